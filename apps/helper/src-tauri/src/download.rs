@@ -0,0 +1,463 @@
+//! Download-to-file for `helper_fetch`.
+//!
+//! Diagnostic bundles and agent installers can run to hundreds of MB, so
+//! instead of buffering the body into the IPC response we stream it straight
+//! to disk. Targets are confined to the user's Downloads folder: the request
+//! names a file relative to it (or an absolute path already inside it), and
+//! anything that would escape — `..`, symlinked parents — is rejected.
+//!
+//! An interrupted download leaves its partial file in place so a `resume`
+//! request can fetch only the missing tail with an HTTP `Range` request.
+//! A finished file can be checked against a published SHA-256, either by the
+//! request itself (`expected_sha256`) or afterwards with `file_sha256`.
+
+use futures_util::StreamExt;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Minimum gap between progress events for one request (~10 per second).
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Emitted as `helper-fetch-progress` while a streamed body is read or a
+/// download is written. Exactly one of `stream_id` and `path` is set.
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchProgressEvent {
+    /// The stream the chunks arrive on, for `stream` requests.
+    pub stream_id: Option<String>,
+    /// The `download_to_path` the request was made with, for downloads.
+    pub path: Option<String>,
+    /// Bytes so far; for a download, the file length including any prefix
+    /// kept by a resume.
+    pub bytes_so_far: u64,
+    /// Expected final length, from `Content-Range` or `Content-Length`; null
+    /// when the body is chunked.
+    pub total_bytes: Option<u64>,
+    /// True on the final event, once the body is complete.
+    pub done: bool,
+}
+
+/// Lets a progress event through at most every `PROGRESS_INTERVAL`.
+pub struct ProgressThrottle {
+    last: Instant,
+}
+
+impl ProgressThrottle {
+    /// The first event is due one interval after `start`.
+    pub fn new(start: Instant) -> Self {
+        Self { last: start }
+    }
+
+    /// True (and restarts the interval) if an event is due at `now`.
+    pub fn ready_at(&mut self, now: Instant) -> bool {
+        if now.saturating_duration_since(self.last) < PROGRESS_INTERVAL {
+            return false;
+        }
+        self.last = now;
+        true
+    }
+}
+
+#[derive(Debug)]
+pub enum DownloadError {
+    /// Reading the response body failed (network drop, deadline).
+    Body(reqwest::Error),
+    /// Writing the file failed.
+    Io(std::io::Error),
+}
+
+/// Lexical check: `requested` must name a file under `downloads_dir`, either
+/// relative to it or as an absolute path inside it, with no `.`/`..`.
+fn validate_download_path(downloads_dir: &Path, requested: &str) -> Result<PathBuf, String> {
+    if requested.is_empty() {
+        return Err("download_to_path must not be empty".to_string());
+    }
+    if requested.contains('\0') {
+        return Err("download_to_path must not contain NUL bytes".to_string());
+    }
+
+    let requested = Path::new(requested);
+    let relative = if requested.is_absolute() {
+        requested
+            .strip_prefix(downloads_dir)
+            .map_err(|_| "download_to_path must be inside the Downloads folder".to_string())?
+    } else {
+        requested
+    };
+
+    let mut components = 0;
+    for component in relative.components() {
+        match component {
+            Component::Normal(_) => components += 1,
+            _ => return Err("download_to_path must not contain . or .. segments".to_string()),
+        }
+    }
+    if components == 0 {
+        return Err("download_to_path must name a file".to_string());
+    }
+
+    Ok(downloads_dir.join(relative))
+}
+
+/// Resolve `requested` to a writable file under `downloads_dir`, creating
+/// intermediate folders. Symlinks are resolved before the containment check,
+/// so a link inside Downloads cannot point the write elsewhere.
+pub fn prepare_download_target(downloads_dir: &Path, requested: &str) -> Result<PathBuf, String> {
+    let target = validate_download_path(downloads_dir, requested)?;
+    let root = downloads_dir
+        .canonicalize()
+        .map_err(|e| format!("Downloads folder is unavailable: {}", e))?;
+
+    let parent = target
+        .parent()
+        .ok_or_else(|| "download_to_path must name a file".to_string())?;
+    // Check the deepest folder that already exists before creating anything,
+    // or `link/new/file` would create `new` wherever `link` points.
+    let existing = parent
+        .ancestors()
+        .find(|dir| dir.exists())
+        .and_then(|dir| dir.canonicalize().ok())
+        .ok_or_else(|| "Downloads folder is unavailable".to_string())?;
+    if !existing.starts_with(&root) {
+        return Err("download_to_path must be inside the Downloads folder".to_string());
+    }
+    std::fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create download folder: {}", e))?;
+    let parent = parent
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve download folder: {}", e))?;
+    if !parent.starts_with(&root) {
+        return Err("download_to_path must be inside the Downloads folder".to_string());
+    }
+
+    if let Ok(meta) = std::fs::symlink_metadata(&target) {
+        if !meta.is_file() {
+            return Err("download_to_path must not be a directory or symlink".to_string());
+        }
+    }
+
+    // `parent` is canonical and `target` has at least one component.
+    Ok(parent.join(target.file_name().unwrap_or_default()))
+}
+
+/// Resolve `requested` to an existing file under `downloads_dir`, for reading
+/// back an earlier download. As with `prepare_download_target`, symlinks are
+/// resolved before the containment check.
+pub fn resolve_existing_download(downloads_dir: &Path, requested: &str) -> Result<PathBuf, String> {
+    let target = validate_download_path(downloads_dir, requested)?;
+    let root = downloads_dir
+        .canonicalize()
+        .map_err(|e| format!("Downloads folder is unavailable: {}", e))?;
+    let target = target
+        .canonicalize()
+        .map_err(|_| "No such file in the Downloads folder".to_string())?;
+    if !target.starts_with(&root) {
+        return Err("path must be inside the Downloads folder".to_string());
+    }
+    if !target.is_file() {
+        return Err("path must name a file".to_string());
+    }
+    Ok(target)
+}
+
+/// Lowercase hex SHA-256 of the file at `path`, read a buffer at a time so a
+/// large installer is never held in memory.
+pub async fn file_sha256(path: &Path) -> std::io::Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// `expected_sha256` as lowercase hex, so it compares equal to `file_sha256`.
+pub fn normalize_sha256(value: &str) -> Result<String, String> {
+    let value = value.trim().to_ascii_lowercase();
+    if value.len() != 64 || !value.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("expected_sha256 must be a hex SHA-256 digest".to_string());
+    }
+    Ok(value)
+}
+
+/// Size of a partial download already at `path`, or 0 if there is none.
+pub fn partial_download_len(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .ok()
+        .filter(|meta| meta.is_file())
+        .map_or(0, |meta| meta.len())
+}
+
+/// Parse a `Content-Range: bytes <first>-<last>/<total>` response header into
+/// `(first, total)`. `total` is `None` when the server sent `*`.
+pub fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let (first, last) = range.split_once('-')?;
+    let first: u64 = first.parse().ok()?;
+    let last: u64 = last.parse().ok()?;
+    if last < first {
+        return None;
+    }
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((first, total))
+}
+
+/// Stream `response`'s body into `path`, calling `on_progress` with
+/// `(file_len, total_bytes)` at most every `PROGRESS_INTERVAL`. With
+/// `offset == 0` the file is truncated; otherwise the body is appended to the
+/// `offset` bytes already there (a `206` resume). Returns the final file
+/// length. A failed download leaves the partial file for a later resume.
+pub async fn write_body_to_file(
+    response: reqwest::Response,
+    path: &Path,
+    offset: u64,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<u64, DownloadError> {
+    let total = response.content_length().map(|len| offset + len);
+    let file = if offset == 0 {
+        tokio::fs::File::create(path).await
+    } else {
+        tokio::fs::OpenOptions::new().append(true).open(path).await
+    };
+    let mut file = file.map_err(DownloadError::Io)?;
+
+    let mut written: u64 = offset;
+    let mut throttle = ProgressThrottle::new(Instant::now());
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.next().await {
+        let chunk = chunk.map_err(DownloadError::Body)?;
+        file.write_all(&chunk).await.map_err(DownloadError::Io)?;
+        written += chunk.len() as u64;
+        if throttle.ready_at(Instant::now()) {
+            on_progress(written, total);
+        }
+    }
+    file.flush().await.map_err(DownloadError::Io)?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn downloads() -> PathBuf {
+        PathBuf::from(if cfg!(windows) {
+            r"C:\Users\u\Downloads"
+        } else {
+            "/home/u/Downloads"
+        })
+    }
+
+    #[test]
+    fn validate_accepts_relative_and_absolute_inside() {
+        let dir = downloads();
+        assert_eq!(
+            validate_download_path(&dir, "bundle.zip").unwrap(),
+            dir.join("bundle.zip")
+        );
+        assert_eq!(
+            validate_download_path(&dir, "breeze/bundle.zip").unwrap(),
+            dir.join("breeze").join("bundle.zip")
+        );
+        let absolute = dir.join("installer.pkg");
+        assert_eq!(
+            validate_download_path(&dir, absolute.to_str().unwrap()).unwrap(),
+            absolute
+        );
+    }
+
+    #[test]
+    fn validate_rejects_traversal_and_outside_paths() {
+        let dir = downloads();
+        assert!(validate_download_path(&dir, "../.ssh/authorized_keys").is_err());
+        assert!(validate_download_path(&dir, "a/../../b").is_err());
+        assert!(validate_download_path(&dir, "./a").is_err());
+        assert!(validate_download_path(&dir, "").is_err());
+        assert!(validate_download_path(&dir, "a\0b").is_err());
+        let outside = if cfg!(windows) {
+            r"C:\Windows\x.dll"
+        } else {
+            "/etc/passwd"
+        };
+        assert!(validate_download_path(&dir, outside).is_err());
+        // The Downloads folder itself is not a file.
+        assert!(validate_download_path(&dir, dir.to_str().unwrap()).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn prepare_rejects_symlinked_parent_escaping_downloads() {
+        let base =
+            std::env::temp_dir().join(format!("breeze-helper-download-{}", std::process::id()));
+        let dir = base.join("Downloads");
+        let outside = base.join("outside");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, dir.join("link")).unwrap();
+
+        let ok = prepare_download_target(&dir, "nested/bundle.zip").unwrap();
+        assert!(ok.starts_with(dir.canonicalize().unwrap()));
+        assert!(ok.parent().unwrap().is_dir());
+        assert!(prepare_download_target(&dir, "link/bundle.zip").is_err());
+        assert!(prepare_download_target(&dir, "link/new/bundle.zip").is_err());
+        assert!(!outside.join("new").exists());
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[tokio::test]
+    async fn file_sha256_hashes_existing_downloads_only() {
+        let base =
+            std::env::temp_dir().join(format!("breeze-helper-sha256-{}", std::process::id()));
+        let dir = base.join("Downloads");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("bundle.zip"), b"abc").unwrap();
+        std::fs::write(base.join("outside.zip"), b"abc").unwrap();
+
+        let path = resolve_existing_download(&dir, "bundle.zip").unwrap();
+        assert_eq!(
+            file_sha256(&path).await.unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(resolve_existing_download(&dir, "missing.zip").is_err());
+        assert!(resolve_existing_download(&dir, "../outside.zip").is_err());
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_existing_download_rejects_symlinks() {
+        let base =
+            std::env::temp_dir().join(format!("breeze-helper-sha256-link-{}", std::process::id()));
+        let dir = base.join("Downloads");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(base.join("outside.zip"), b"abc").unwrap();
+        std::os::unix::fs::symlink(base.join("outside.zip"), dir.join("link.zip")).unwrap();
+
+        assert!(resolve_existing_download(&dir, "link.zip").is_err());
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn normalize_sha256_lowercases_and_rejects_non_digests() {
+        let upper = "BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD";
+        assert_eq!(normalize_sha256(upper).unwrap(), upper.to_ascii_lowercase());
+        assert!(normalize_sha256("ba7816bf").is_err());
+        assert!(normalize_sha256(&"g".repeat(64)).is_err());
+    }
+
+    #[tokio::test]
+    async fn write_body_streams_response_to_file() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server addr");
+        let payload = vec![0xA5u8; 256 * 1024];
+        let body = payload.clone();
+        tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.expect("accept");
+            let mut buf = vec![0u8; 4096];
+            let _ = sock.read(&mut buf).await.expect("read request");
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            sock.write_all(head.as_bytes()).await.expect("write head");
+            sock.write_all(&body).await.expect("write body");
+        });
+
+        let path = std::env::temp_dir().join(format!(
+            "breeze-helper-download-body-{}.bin",
+            std::process::id()
+        ));
+        let response = reqwest::get(format!("http://{}/bundle.zip", addr))
+            .await
+            .expect("request");
+        let mut last_total = None;
+        let written = write_body_to_file(response, &path, 0, |_, total| last_total = Some(total))
+            .await
+            .expect("download");
+
+        assert_eq!(written, payload.len() as u64);
+        assert_eq!(std::fs::read(&path).unwrap(), payload);
+        if let Some(total) = last_total {
+            assert_eq!(total, Some(payload.len() as u64));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn progress_throttle_allows_about_ten_per_second() {
+        let start = Instant::now();
+        let mut throttle = ProgressThrottle::new(start);
+        assert!(!throttle.ready_at(start + Duration::from_millis(50)));
+        assert!(throttle.ready_at(start + Duration::from_millis(100)));
+        assert!(!throttle.ready_at(start + Duration::from_millis(150)));
+        let emitted = (1..=100)
+            .filter(|ms| throttle.ready_at(start + Duration::from_millis(ms * 10)))
+            .count();
+        assert_eq!(emitted, 9);
+    }
+
+    #[test]
+    fn parse_content_range_reads_offset_and_total() {
+        assert_eq!(
+            parse_content_range("bytes 100-199/1000"),
+            Some((100, Some(1000)))
+        );
+        assert_eq!(parse_content_range("bytes 0-0/*"), Some((0, None)));
+        assert_eq!(parse_content_range("bytes */1000"), None);
+        assert_eq!(parse_content_range("bytes 9-3/10"), None);
+        assert_eq!(parse_content_range("items 0-9/10"), None);
+    }
+
+    #[tokio::test]
+    async fn write_body_appends_after_offset() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server addr");
+        tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.expect("accept");
+            let mut buf = vec![0u8; 4096];
+            let _ = sock.read(&mut buf).await.expect("read request");
+            sock.write_all(
+                b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 5-9/10\r\nContent-Length: 5\r\nConnection: close\r\n\r\nworld",
+            )
+            .await
+            .expect("write response");
+        });
+
+        let path = std::env::temp_dir().join(format!(
+            "breeze-helper-download-resume-{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, b"hello").unwrap();
+        let response = reqwest::get(format!("http://{}/bundle.zip", addr))
+            .await
+            .expect("request");
+        let len = write_body_to_file(response, &path, 5, |_, _| {})
+            .await
+            .expect("download");
+
+        assert_eq!(len, 10);
+        assert_eq!(std::fs::read(&path).unwrap(), b"helloworld");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
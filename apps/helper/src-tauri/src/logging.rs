@@ -0,0 +1,294 @@
+//! Rotating request log for field debugging.
+//!
+//! Once the helper runs detached, `eprintln!` goes nowhere. `init` installs a
+//! `tracing` subscriber that writes to `breeze-helper.log` under the platform
+//! log directory: one line per `helper_fetch` at `info`, plus the outgoing
+//! request (URL and headers, secrets redacted) at `debug`. Verbosity comes from
+//! `BREEZE_HELPER_LOG`. The file rotates by size and only a few old files are
+//! kept, so the log can never grow past `MAX_LOG_BYTES * (KEEP_LOG_FILES + 1)`.
+
+use reqwest::header::HeaderMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_subscriber::filter::LevelFilter;
+
+const LOG_FILE_NAME: &str = "breeze-helper.log";
+const MAX_LOG_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept alongside the live one (`.1` is the newest).
+const KEEP_LOG_FILES: usize = 3;
+
+/// Flushes buffered lines on drop; held for the life of the process.
+static LOG_GUARD: OnceLock<tracing_appender::non_blocking::WorkerGuard> = OnceLock::new();
+/// The live log file, once `init` has opened it.
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Install the file logger. Call once, from setup.
+pub fn init(log_dir: &Path) -> Result<(), String> {
+    let level = level_from_env(std::env::var("BREEZE_HELPER_LOG").ok().as_deref());
+    std::fs::create_dir_all(log_dir)
+        .map_err(|e| format!("Failed to create log folder {}: {}", log_dir.display(), e))?;
+    let path = log_dir.join(LOG_FILE_NAME);
+    let file = RotatingFile::open(path.clone(), MAX_LOG_BYTES, KEEP_LOG_FILES)
+        .map_err(|e| format!("Failed to open helper log: {}", e))?;
+    let (writer, guard) = tracing_appender::non_blocking(file);
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_target(false)
+        .try_init()
+        .map_err(|e| format!("Failed to install helper log: {}", e))?;
+    let _ = LOG_GUARD.set(guard);
+    let _ = LOG_PATH.set(path);
+    Ok(())
+}
+
+/// `BREEZE_HELPER_LOG` as a level; unset or unrecognized means `info`.
+fn level_from_env(value: Option<&str>) -> LevelFilter {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(LevelFilter::INFO)
+}
+
+/// The last `max` lines written, oldest first, reaching back into the rotated
+/// files when the live one is shorter.
+pub fn recent_lines(max: usize) -> Result<Vec<String>, String> {
+    let path = LOG_PATH
+        .get()
+        .ok_or_else(|| "The helper log is not available".to_string())?;
+    let mut paths: Vec<PathBuf> = (1..=KEEP_LOG_FILES)
+        .rev()
+        .map(|n| rotated_path(path, n))
+        .collect();
+    paths.push(path.clone());
+    tail_lines(&paths, max).map_err(|e| format!("Failed to read helper log: {}", e))
+}
+
+/// The last `max` lines across `paths`, oldest file first. Missing files
+/// count as empty, and older files are only read while more lines are needed.
+pub fn tail_lines(paths: &[PathBuf], max: usize) -> std::io::Result<Vec<String>> {
+    let mut newest_first = Vec::new();
+    for path in paths.iter().rev() {
+        let wanted = max.saturating_sub(newest_first.len());
+        if wanted == 0 {
+            break;
+        }
+        match std::fs::read(path) {
+            Ok(bytes) => newest_first.extend(
+                String::from_utf8_lossy(&bytes)
+                    .lines()
+                    .rev()
+                    .take(wanted)
+                    .map(str::to_string),
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    newest_first.reverse();
+    Ok(newest_first)
+}
+
+/// Path and query of `url` with credential-bearing query values replaced by
+/// `***` (tokens, and the signatures on presigned object-storage URLs).
+pub fn redact_url(url: &reqwest::Url) -> String {
+    let mut out = url.path().to_string();
+    let Some(query) = url.query() else {
+        return out;
+    };
+    out.push('?');
+    for (i, pair) in query.split('&').enumerate() {
+        if i > 0 {
+            out.push('&');
+        }
+        let (name, _) = pair.split_once('=').unwrap_or((pair, ""));
+        if is_secret_param(name) {
+            out.push_str(name);
+            out.push_str("=***");
+        } else {
+            out.push_str(pair);
+        }
+    }
+    out
+}
+
+fn is_secret_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["token", "signature", "secret", "password"]
+        .iter()
+        .any(|secret| name.contains(secret))
+        || name == "sig"
+        || name == "key"
+}
+
+/// Headers as `name: value` pairs for the debug log, with `Authorization`
+/// reduced to its scheme (`Bearer ***`) and cookies hidden.
+pub fn redact_headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = match name.as_str() {
+                "authorization" | "proxy-authorization" => {
+                    let scheme = value
+                        .to_str()
+                        .ok()
+                        .and_then(|value| value.split_once(' '))
+                        .map(|(scheme, _)| scheme);
+                    match scheme {
+                        Some(scheme) => format!("{} ***", scheme),
+                        None => "***".to_string(),
+                    }
+                }
+                "cookie" => "***".to_string(),
+                _ => value.to_str().unwrap_or("<binary>").to_string(),
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.to_path_buf().into_os_string();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Append-only file that rolls over to `<name>.1` once it would pass
+/// `max_bytes`, shifting older files up and deleting the oldest.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, keep: usize) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes,
+            keep,
+        })
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        rotated_path(&self.path, n)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        let _ = std::fs::remove_file(self.rotated(self.keep));
+        for n in (1..self.keep).rev() {
+            let _ = std::fs::rename(self.rotated(n), self.rotated(n + 1));
+        }
+        if self.keep > 0 {
+            std::fs::rename(&self.path, self.rotated(1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_defaults_to_info() {
+        assert_eq!(level_from_env(None), LevelFilter::INFO);
+        assert_eq!(level_from_env(Some("debug")), LevelFilter::DEBUG);
+        assert_eq!(level_from_env(Some(" TRACE ")), LevelFilter::TRACE);
+        assert_eq!(level_from_env(Some("loud")), LevelFilter::INFO);
+    }
+
+    #[test]
+    fn redact_url_hides_token_and_signature_params() {
+        let url = reqwest::Url::parse(
+            "https://api.example.test/api/v1/x?token=abc&page=2&X-Amz-Signature=def&access_token=ghi",
+        )
+        .unwrap();
+        assert_eq!(
+            redact_url(&url),
+            "/api/v1/x?token=***&page=2&X-Amz-Signature=***&access_token=***"
+        );
+        let plain = reqwest::Url::parse("https://api.example.test/healthz").unwrap();
+        assert_eq!(redact_url(&plain), "/healthz");
+    }
+
+    #[test]
+    fn redact_headers_keeps_only_the_auth_scheme() {
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer secret-token".parse().unwrap());
+        headers.insert("cookie", "session=secret".parse().unwrap());
+        headers.insert("accept", "application/json".parse().unwrap());
+        let logged = redact_headers(&headers);
+        assert!(logged.contains("authorization: Bearer ***"), "{logged}");
+        assert!(logged.contains("cookie: ***"), "{logged}");
+        assert!(logged.contains("accept: application/json"), "{logged}");
+        assert!(!logged.contains("secret"), "{logged}");
+    }
+
+    #[test]
+    fn rotating_file_caps_size_and_count() {
+        let dir = std::env::temp_dir().join(format!("breeze-helper-log-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOG_FILE_NAME);
+
+        let mut file = RotatingFile::open(path.clone(), 100, 2).unwrap();
+        for _ in 0..20 {
+            file.write_all(&[b'x'; 40]).unwrap();
+        }
+        file.flush().unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() <= 100);
+        assert!(dir.join(format!("{}.1", LOG_FILE_NAME)).exists());
+        assert!(dir.join(format!("{}.2", LOG_FILE_NAME)).exists());
+        assert!(!dir.join(format!("{}.3", LOG_FILE_NAME)).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tail_lines_reaches_into_older_files_only_when_needed() {
+        let dir = std::env::temp_dir().join(format!("breeze-helper-tail-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOG_FILE_NAME);
+        std::fs::write(rotated_path(&path, 1), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(&path, "four\nfive\n").unwrap();
+
+        let files = [rotated_path(&path, 2), rotated_path(&path, 1), path.clone()];
+        assert_eq!(tail_lines(&files, 3).unwrap(), ["three", "four", "five"]);
+        assert_eq!(tail_lines(&files, 2).unwrap(), ["four", "five"]);
+        assert_eq!(tail_lines(&files, 100).unwrap().len(), 5);
+        assert!(tail_lines(&files, 0).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
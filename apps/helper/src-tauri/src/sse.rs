@@ -0,0 +1,191 @@
+//! Server-Sent Events framing for streamed `helper_fetch` bodies.
+//!
+//! With `parse_sse: true` the helper splits the decoded body into events
+//! itself, following the WHATWG event-stream rules: lines end in CRLF, LF or
+//! a lone CR; `:` starts a comment; `data:` lines accumulate; a blank line
+//! dispatches. The frontend then gets one `StreamChunkEvent` per event
+//! instead of reassembling frames out of arbitrary network chunks.
+
+use serde::Serialize;
+
+/// One dispatched event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SseEvent {
+    /// The `event:` name; `"message"` when the server sent none.
+    pub event: String,
+    /// All `data:` lines of the event, joined with `\n`.
+    pub data: String,
+    /// The last event ID seen on the stream so far, if any.
+    pub id: Option<String>,
+}
+
+/// Incremental parser: feed decoded text as it arrives, get back the events
+/// it completed.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    line: String,
+    /// The previous push ended in `\r`; a leading `\n` belongs to that CRLF.
+    after_cr: bool,
+    started: bool,
+    event: Option<String>,
+    data: String,
+    has_data: bool,
+    last_id: Option<String>,
+    retry: Option<u64>,
+}
+
+impl SseParser {
+    pub fn push(&mut self, text: &str) -> Vec<SseEvent> {
+        let mut text = text;
+        if !self.started && !text.is_empty() {
+            self.started = true;
+            text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        }
+
+        let mut events = Vec::new();
+        for ch in text.chars() {
+            match ch {
+                '\n' if self.after_cr => self.after_cr = false,
+                '\r' | '\n' => {
+                    self.after_cr = ch == '\r';
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(event) = self.process_line(&line) {
+                        events.push(event);
+                    }
+                }
+                _ => {
+                    self.after_cr = false;
+                    self.line.push(ch);
+                }
+            }
+        }
+        events
+    }
+
+    /// The last event ID seen, for a `Last-Event-ID` reconnect header.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_id.as_deref()
+    }
+
+    /// The most recent `retry:` reconnection interval, in milliseconds.
+    pub fn retry_ms(&self) -> Option<u64> {
+        self.retry
+    }
+
+    /// Start over on a new connection: a half-received event from the old
+    /// one is discarded, but the last event ID and `retry:` carry over.
+    pub fn reconnected(&mut self) {
+        *self = Self {
+            last_id: self.last_id.take(),
+            retry: self.retry,
+            ..Self::default()
+        };
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => {
+                if self.has_data {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value);
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if !std::mem::take(&mut self.has_data) {
+            return None;
+        }
+        Some(SseEvent {
+            event: event
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "message".to_string()),
+            data: std::mem::take(&mut self.data),
+            id: self.last_id.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_all(chunks: &[&str]) -> Vec<SseEvent> {
+        let mut parser = SseParser::default();
+        chunks.iter().flat_map(|chunk| parser.push(chunk)).collect()
+    }
+
+    #[test]
+    fn parses_event_name_id_and_multiline_data() {
+        let events = parse_all(&["event: delta\nid: 7\ndata: a\ndata: b\n\ndata: c\n\n"]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event, "delta");
+        assert_eq!(events[0].data, "a\nb");
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+        assert_eq!(events[1].event, "message");
+        assert_eq!(events[1].data, "c");
+        // The last event ID carries over to later events.
+        assert_eq!(events[1].id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn handles_crlf_split_across_chunks_and_lone_cr() {
+        let events = parse_all(&["data: one\r", "\n\r", "\ndata: two\r\rdata:three\n\n"]);
+        let data: Vec<_> = events.iter().map(|e| e.data.as_str()).collect();
+        assert_eq!(data, ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn skips_comments_and_incomplete_trailing_event() {
+        let mut parser = SseParser::default();
+        assert!(parser.push("\u{feff}: keep-alive\n\n").is_empty());
+        assert!(parser.push("data: {\"x\":").is_empty());
+        let events = parser.push("1}\n\ndata: tail");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "{\"x\":1}");
+    }
+
+    #[test]
+    fn ignores_unknown_fields_and_ids_with_nul() {
+        let events = parse_all(&["retry: 3000\nid: a\0b\nbogus: x\ndata\n\n"]);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].id, None);
+        assert_eq!(events[0].data, "");
+    }
+
+    #[test]
+    fn reconnect_keeps_last_id_and_retry_but_drops_partial_event() {
+        let mut parser = SseParser::default();
+        let events = parser.push("retry: 2500\nid: 41\ndata: a\n\nid: 42\ndata: cut");
+        assert_eq!(events.len(), 1);
+        assert_eq!(parser.retry_ms(), Some(2500));
+        assert_eq!(parser.last_event_id(), Some("42"));
+
+        parser.reconnected();
+        let events = parser.push("data: b\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "b");
+        assert_eq!(events[0].id.as_deref(), Some("42"));
+        assert_eq!(parser.retry_ms(), Some(2500));
+    }
+}
@@ -25,7 +25,7 @@ pub enum Platform {
     Linux,
 }
 
-fn current_platform() -> Platform {
+pub fn current_platform() -> Platform {
     #[cfg(target_os = "macos")]
     {
         Platform::MacOs
@@ -132,10 +132,7 @@ pub async fn open_workspace_path(
     tauri_plugin_shell::ShellExt::shell(&app)
         .open(&target, None::<open::Program>)
         .map_err(|e| {
-            crate::log_helper_error(&format!(
-                "[helper] Failed to open workspace path {}: {}",
-                target, e
-            ));
+            crate::log_helper_error(&format!("Failed to open workspace path {}: {}", target, e));
             "Couldn't open the file on this machine.".to_string()
         })
 }
@@ -0,0 +1,568 @@
+//! WebSocket proxy for the frontend.
+//!
+//! Parts of the API push live data over WebSockets (command output), which
+//! the webview cannot open with the helper's token or mTLS identity.
+//! `helper_ws_connect` opens the socket here instead, vetted exactly like a
+//! `helper_fetch` URL — the handshake is an HTTP GET, so `ws`/`wss` URLs are
+//! checked as their `http`/`https` equivalents — and relays inbound frames as
+//! `helper-ws-message` events keyed by `ws_id`. Sockets live in a registry
+//! shaped like the stream registry: whoever removes an entry owns the
+//! terminal event.
+
+use crate::{
+    api_cookie_jar, fetch_target, helper_token, helper_user_agent, http_config_snapshot,
+    is_cert_pin_mismatch, is_tls_error, log_helper_error, mtls_identity_pem, proxy_settings,
+    rustls_client_config, AgentConfigFull, FetchTarget, HelperError, CERT_PIN_MISMATCH,
+    DEFAULT_CONNECT_TIMEOUT,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use futures_util::{SinkExt, StreamExt};
+use reqwest::cookie::CookieStore;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use tauri::{AppHandle, Emitter};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream};
+
+/// How long `helper_ws_close` lets the close frame flush before the socket
+/// task is aborted.
+const WS_CLOSE_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Handshake headers the caller may not set; tungstenite derives them from
+/// the URL and the upgrade itself.
+const RESERVED_HEADERS: [&str; 7] = [
+    "authorization",
+    "host",
+    "connection",
+    "upgrade",
+    "sec-websocket-key",
+    "sec-websocket-version",
+    "sec-websocket-extensions",
+];
+
+/// Emitted as `helper-ws-message` for every inbound text or binary frame, and
+/// once more with `done: true` when the socket is gone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WsMessageEvent {
+    pub ws_id: String,
+    /// Frame payload: text as-is, binary base64-encoded. Null on the terminal
+    /// event.
+    pub data: Option<String>,
+    /// True when `data` is base64 of a binary frame.
+    pub binary: bool,
+    /// True when this is the final event for this socket.
+    pub done: bool,
+    /// On the terminal event: the close code the server (or
+    /// `helper_ws_close`) sent, if any.
+    pub close_code: Option<u16>,
+    pub close_reason: Option<String>,
+    /// Non-null when the socket failed rather than closed.
+    pub error: Option<String>,
+}
+
+impl WsMessageEvent {
+    fn text(ws_id: &str, text: String) -> Self {
+        Self::frame(ws_id, text, false)
+    }
+
+    fn binary(ws_id: &str, bytes: &[u8]) -> Self {
+        Self::frame(ws_id, BASE64.encode(bytes), true)
+    }
+
+    fn frame(ws_id: &str, data: String, binary: bool) -> Self {
+        Self {
+            ws_id: ws_id.to_string(),
+            data: Some(data),
+            binary,
+            done: false,
+            close_code: None,
+            close_reason: None,
+            error: None,
+        }
+    }
+
+    fn done(ws_id: &str, close: Option<&CloseFrame>, error: Option<String>) -> Self {
+        Self {
+            ws_id: ws_id.to_string(),
+            data: None,
+            binary: false,
+            done: true,
+            close_code: close.map(|frame| u16::from(frame.code)),
+            close_reason: close
+                .map(|frame| frame.reason.to_string())
+                .filter(|reason| !reason.is_empty()),
+            error,
+        }
+    }
+}
+
+fn emit_ws_event(app: &AppHandle, event: WsMessageEvent) {
+    if let Err(e) = app.emit("helper-ws-message", &event) {
+        eprintln!("[helper] Failed to emit WebSocket event: {}", e);
+    }
+}
+
+// -- live socket registry ---------------------------------------------------
+
+/// An open socket: frames queued for it, and the task that owns it.
+struct LiveSocket {
+    outgoing: mpsc::UnboundedSender<Message>,
+    task: tokio::task::AbortHandle,
+}
+
+/// Open sockets keyed by `ws_id`. As with `STREAMS`, removing an entry
+/// claims the terminal event — the task when the socket ends,
+/// `helper_ws_close`, or `stop_all_sockets` — so the frontend sees exactly
+/// one `done: true`.
+static SOCKETS: OnceLock<std::sync::Mutex<HashMap<String, LiveSocket>>> = OnceLock::new();
+
+fn live_sockets() -> std::sync::MutexGuard<'static, HashMap<String, LiveSocket>> {
+    SOCKETS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Remove a finished socket. Returns false if `helper_ws_close` got there
+/// first, in which case the terminal event has already been sent.
+fn deregister_socket(ws_id: &str) -> bool {
+    live_sockets().remove(ws_id).is_some()
+}
+
+/// Random, like `new_stream_id`, so IDs can't be guessed.
+fn new_ws_id() -> String {
+    format!("ws-{}", uuid::Uuid::new_v4())
+}
+
+/// The `http(s)` URL a `ws(s)` URL is vetted as.
+fn http_equivalent(url: &reqwest::Url) -> Result<reqwest::Url, String> {
+    let mut url = url.clone();
+    let scheme = match url.scheme() {
+        "ws" => "http",
+        "wss" => "https",
+        _ => return Err("WebSocket URL must use ws or wss".to_string()),
+    };
+    url.set_scheme(scheme)
+        .map_err(|_| "WebSocket URL is invalid".to_string())?;
+    Ok(url)
+}
+
+/// Whether `host` is exempt from the proxy under a `NO_PROXY`-style list:
+/// `*`, the host itself, or a parent domain (with or without a leading dot).
+fn proxy_bypassed(host: &str, no_proxy: Option<&str>) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    no_proxy.unwrap_or_default().split(',').any(|entry| {
+        let entry = entry.trim().trim_start_matches('.').to_ascii_lowercase();
+        !entry.is_empty()
+            && (entry == "*"
+                || host == entry
+                || host
+                    .strip_suffix(&entry)
+                    .is_some_and(|rest| rest.ends_with('.')))
+    })
+}
+
+/// The handshake request for `url` (`ws`/`wss`): the caller's headers, then the helper's
+/// `User-Agent`, API cookies, and — for the API only — the bearer token,
+/// set last so none of them can be overridden.
+fn handshake_request(
+    url: &reqwest::Url,
+    target: &FetchTarget,
+    token: &str,
+    cookies: Option<reqwest::header::HeaderValue>,
+    headers: &HashMap<String, String>,
+) -> Result<tokio_tungstenite::tungstenite::handshake::client::Request, HelperError> {
+    let invalid = |message: String| HelperError::Internal { message };
+    let mut request = url
+        .as_str()
+        .into_client_request()
+        .map_err(|e| invalid(format!("WebSocket URL is invalid: {}", e)))?;
+    let request_headers = request.headers_mut();
+    for (k, v) in headers {
+        if RESERVED_HEADERS
+            .iter()
+            .any(|name| k.eq_ignore_ascii_case(name))
+        {
+            continue;
+        }
+        let name = k
+            .parse::<http::HeaderName>()
+            .map_err(|e| invalid(format!("Invalid header name '{}': {}", k, e)))?;
+        let val = v
+            .parse::<http::HeaderValue>()
+            .map_err(|e| invalid(format!("Invalid header value for '{}': {}", k, e)))?;
+        request_headers.insert(name, val);
+    }
+    if !request_headers.contains_key(http::header::USER_AGENT) {
+        let agent = helper_user_agent()
+            .parse()
+            .map_err(|e| invalid(format!("Invalid User-Agent: {}", e)))?;
+        request_headers.insert(http::header::USER_AGENT, agent);
+    }
+    if let (FetchTarget::Api, Some(cookies)) = (target, cookies) {
+        request_headers.insert(http::header::COOKIE, cookies);
+    }
+    if *target == FetchTarget::Api {
+        let bearer = format!("Bearer {}", token)
+            .parse()
+            .map_err(|e| invalid(format!("Invalid auth token: {}", e)))?;
+        request_headers.insert(http::header::AUTHORIZATION, bearer);
+    }
+    Ok(request)
+}
+
+/// Classify a failed handshake like `request_error` does a failed send.
+fn connect_error(error: &tokio_tungstenite::tungstenite::Error) -> HelperError {
+    use tokio_tungstenite::tungstenite::Error;
+    match error {
+        _ if is_cert_pin_mismatch(error) => HelperError::Tls {
+            message: CERT_PIN_MISMATCH.to_string(),
+        },
+        _ if is_tls_error(error) => HelperError::Tls {
+            message: "Cannot establish a secure connection to the Breeze server. Contact your administrator.".to_string(),
+        },
+        Error::Http(response) => HelperError::HttpStatus {
+            status: response.status().as_u16(),
+            message: format!(
+                "WebSocket upgrade was refused with HTTP {}",
+                response.status().as_u16()
+            ),
+        },
+        _ => HelperError::Network {
+            message: "Cannot connect to the Breeze server. Check your network connection."
+                .to_string(),
+        },
+    }
+}
+
+/// Vet `request_url` against the config and complete the handshake.
+async fn open_socket(
+    cfg: &AgentConfigFull,
+    token: &str,
+    request_url: &str,
+    headers: &HashMap<String, String>,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, HelperError> {
+    let ssrf = |message: String| HelperError::SsrfBlocked { message };
+    let ws_url = reqwest::Url::parse(request_url)
+        .map_err(|e| ssrf(format!("WebSocket URL is invalid: {}", e)))?;
+    let http_url = http_equivalent(&ws_url).map_err(ssrf)?;
+    let target =
+        fetch_target(&cfg.api_url, &cfg.extra_allowed_hosts, http_url.as_str()).map_err(ssrf)?;
+
+    // The tunnel `helper_fetch` gets from reqwest isn't available here, and
+    // silently bypassing a configured proxy could route around egress policy.
+    let host = http_url.host_str().unwrap_or_default();
+    if let Some((_, no_proxy)) =
+        proxy_settings(cfg.proxy_url.as_deref(), cfg.no_proxy.as_deref(), |name| {
+            std::env::var(name).ok()
+        })
+    {
+        if !proxy_bypassed(host, no_proxy.as_deref()) {
+            return Err(HelperError::Network {
+                message: "WebSocket connections through a proxy are not supported".to_string(),
+            });
+        }
+    }
+
+    let cookies = api_cookie_jar(&cfg.api_url).cookies(&http_url);
+    let request = handshake_request(&ws_url, &target, token, cookies, headers)?;
+
+    let tls = |message: String| HelperError::Tls { message };
+    let connector = if http_url.scheme() == "https" {
+        let identity_pem = mtls_identity_pem(cfg).map_err(|e| {
+            log_helper_error(&e);
+            tls(e)
+        })?;
+        let config = rustls_client_config(cfg, identity_pem.as_ref()).map_err(tls)?;
+        Some(Connector::Rustls(Arc::new(config)))
+    } else {
+        None
+    };
+
+    let connect_timeout = cfg.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT);
+    let handshake =
+        tokio_tungstenite::connect_async_tls_with_config(request, None, false, connector);
+    match tokio::time::timeout(connect_timeout, handshake).await {
+        Ok(Ok((socket, _response))) => Ok(socket),
+        Ok(Err(e)) => {
+            log_helper_error(&format!("WebSocket connect failed: {}", e));
+            Err(connect_error(&e))
+        }
+        Err(_) => Err(HelperError::ConnectTimeout {
+            message: "Cannot reach the Breeze server. Check your network connection.".to_string(),
+        }),
+    }
+}
+
+/// Pump frames both ways until the socket ends or `helper_ws_close` drops
+/// the outgoing queue.
+async fn run_socket(
+    app: AppHandle,
+    ws_id: String,
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    mut outgoing: mpsc::UnboundedReceiver<Message>,
+) {
+    let (mut sink, mut inbound) = socket.split();
+    let mut close = None;
+    let error = loop {
+        tokio::select! {
+            queued = outgoing.recv() => match queued {
+                Some(message) => {
+                    if let Err(e) = sink.send(message).await {
+                        break Some(format!("WebSocket send failed: {}", e));
+                    }
+                }
+                // Closed by `helper_ws_close`, which sent the terminal event.
+                None => {
+                    let _ = sink.close().await;
+                    return;
+                }
+            },
+            frame = inbound.next() => match frame {
+                Some(Ok(Message::Text(text))) => {
+                    emit_ws_event(&app, WsMessageEvent::text(&ws_id, text.to_string()));
+                }
+                Some(Ok(Message::Binary(bytes))) => {
+                    emit_ws_event(&app, WsMessageEvent::binary(&ws_id, &bytes));
+                }
+                // tungstenite queues the close reply; keep reading until the
+                // stream ends so it is flushed.
+                Some(Ok(Message::Close(frame))) => close = frame,
+                // Pings are answered by tungstenite.
+                Some(Ok(_)) => {}
+                Some(Err(e)) => break Some(format!("WebSocket read error: {}", e)),
+                None => break None,
+            },
+        }
+    };
+    if deregister_socket(&ws_id) {
+        emit_ws_event(&app, WsMessageEvent::done(&ws_id, close.as_ref(), error));
+    }
+}
+
+/// Open a WebSocket to the API (or an `extra_allowed_hosts` host) through the
+/// helper's client identity. Returns the `ws_id` that keys its
+/// `helper-ws-message` events.
+#[tauri::command]
+pub async fn helper_ws_connect(
+    app: AppHandle,
+    url: String,
+    headers: Option<HashMap<String, String>>,
+) -> Result<String, HelperError> {
+    let cfg = http_config_snapshot().await?;
+    let token = helper_token()
+        .get()
+        .await
+        .unwrap_or_else(|| cfg.token.clone());
+    let socket = open_socket(&cfg, &token, &url, &headers.unwrap_or_default()).await?;
+
+    let ws_id = new_ws_id();
+    let (outgoing, queued) = mpsc::unbounded_channel();
+    // Held across spawn + insert so the task cannot deregister first.
+    let mut sockets = live_sockets();
+    let task = tauri::async_runtime::spawn(run_socket(app, ws_id.clone(), socket, queued));
+    sockets.insert(
+        ws_id.clone(),
+        LiveSocket {
+            outgoing,
+            task: task.inner().abort_handle(),
+        },
+    );
+    Ok(ws_id)
+}
+
+/// Queue a frame on an open socket: `text`, or `binary_base64` decoded to a
+/// binary frame.
+#[tauri::command]
+pub fn helper_ws_send(
+    ws_id: String,
+    text: Option<String>,
+    binary_base64: Option<String>,
+) -> Result<(), HelperError> {
+    let invalid = |message: String| HelperError::Internal { message };
+    let message = match (text, binary_base64) {
+        (Some(text), None) => Message::text(text),
+        (None, Some(encoded)) => Message::binary(
+            BASE64
+                .decode(encoded)
+                .map_err(|e| invalid(format!("Invalid binary_base64: {}", e)))?,
+        ),
+        _ => {
+            return Err(invalid(
+                "Send exactly one of text and binary_base64".to_string(),
+            ))
+        }
+    };
+    let sockets = live_sockets();
+    let socket = sockets
+        .get(&ws_id)
+        .ok_or_else(|| invalid("WebSocket is not open".to_string()))?;
+    socket
+        .outgoing
+        .send(message)
+        .map_err(|_| invalid("WebSocket is not open".to_string()))
+}
+
+/// Close a socket with a normal (1000) close frame. Emits the terminal event
+/// right away; the frame gets `WS_CLOSE_GRACE` to flush. Returns false when
+/// the socket is unknown or already closed.
+#[tauri::command]
+pub fn helper_ws_close(app: AppHandle, ws_id: String) -> bool {
+    let Some(socket) = live_sockets().remove(&ws_id) else {
+        return false;
+    };
+    let frame = CloseFrame {
+        code: CloseCode::Normal,
+        reason: "".into(),
+    };
+    // Dropping the sender after the close frame tells the task to finish.
+    let _ = socket.outgoing.send(Message::Close(Some(frame.clone())));
+    let task = socket.task;
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WS_CLOSE_GRACE).await;
+        task.abort();
+    });
+    emit_ws_event(&app, WsMessageEvent::done(&ws_id, Some(&frame), None));
+    true
+}
+
+/// Stop every open socket for shutdown: queue a going-away (1001) close frame
+/// and send the terminal event with `error: "shutting down"`. Returns the
+/// socket tasks, which finish once the frame is flushed.
+pub fn stop_all_sockets(app: &AppHandle) -> Vec<tokio::task::AbortHandle> {
+    let sockets: Vec<(String, LiveSocket)> = live_sockets().drain().collect();
+    let frame = CloseFrame {
+        code: CloseCode::Away,
+        reason: "".into(),
+    };
+    sockets
+        .into_iter()
+        .map(|(ws_id, socket)| {
+            let _ = socket.outgoing.send(Message::Close(Some(frame.clone())));
+            emit_ws_event(
+                app,
+                WsMessageEvent::done(&ws_id, Some(&frame), Some("shutting down".to_string())),
+            );
+            socket.task
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn http_equivalent_maps_ws_schemes_only() {
+        let http = |url: &str| http_equivalent(&reqwest::Url::parse(url).unwrap());
+        assert_eq!(
+            http("wss://api.example.com/ws/commands?x=1")
+                .unwrap()
+                .as_str(),
+            "https://api.example.com/ws/commands?x=1"
+        );
+        assert_eq!(
+            http("ws://127.0.0.1:8080/ws").unwrap().as_str(),
+            "http://127.0.0.1:8080/ws"
+        );
+        assert!(http("https://api.example.com/ws").is_err());
+    }
+
+    #[test]
+    fn proxy_bypassed_matches_hosts_and_parent_domains() {
+        let list = Some("localhost, .corp.test,api.example.com");
+        assert!(proxy_bypassed("localhost", list));
+        assert!(proxy_bypassed("relay.corp.test", list));
+        assert!(proxy_bypassed("corp.test", list));
+        assert!(proxy_bypassed("API.example.com.", list));
+        assert!(!proxy_bypassed("evilcorp.test", list));
+        assert!(!proxy_bypassed("example.com", list));
+        assert!(proxy_bypassed("anything", Some("*")));
+        assert!(!proxy_bypassed("localhost", None));
+    }
+
+    #[test]
+    fn handshake_sends_the_token_to_the_api_only() {
+        let url = reqwest::Url::parse("wss://api.example.com/ws").unwrap();
+        let headers = HashMap::from([
+            ("Authorization".to_string(), "Bearer forged".to_string()),
+            ("Sec-WebSocket-Key".to_string(), "forged".to_string()),
+            ("X-Trace".to_string(), "abc".to_string()),
+        ]);
+        let cookie = Some(reqwest::header::HeaderValue::from_static("sid=1"));
+
+        let request =
+            handshake_request(&url, &FetchTarget::Api, "secret", cookie.clone(), &headers).unwrap();
+        let sent = request.headers();
+        assert_eq!(sent["authorization"], "Bearer secret");
+        assert_eq!(sent["cookie"], "sid=1");
+        assert_eq!(sent["x-trace"], "abc");
+        assert_ne!(sent["sec-websocket-key"], "forged");
+        assert!(sent["user-agent"]
+            .to_str()
+            .unwrap()
+            .starts_with("BreezeHelper/"));
+
+        let relay = reqwest::Url::parse("wss://relay.example.net/ws").unwrap();
+        let request = handshake_request(
+            &relay,
+            &FetchTarget::ExtraHost(http_equivalent(&relay).unwrap()),
+            "secret",
+            cookie,
+            &headers,
+        )
+        .unwrap();
+        assert!(!request.headers().contains_key("authorization"));
+        assert!(!request.headers().contains_key("cookie"));
+    }
+
+    // tungstenite's handshake callback fixes the large error type.
+    #[allow(clippy::result_large_err)]
+    #[tokio::test]
+    async fn open_socket_vets_the_url_and_authenticates_the_handshake() {
+        use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server addr");
+        let server = tokio::spawn(async move {
+            let (sock, _) = listener.accept().await.expect("accept");
+            let mut auth = None;
+            let mut socket =
+                tokio_tungstenite::accept_hdr_async(sock, |req: &Request, resp: Response| {
+                    auth = req
+                        .headers()
+                        .get("authorization")
+                        .map(|v| v.to_str().unwrap().to_string());
+                    Ok(resp)
+                })
+                .await
+                .expect("handshake");
+            let echo = socket.next().await.expect("frame").expect("read");
+            socket.send(echo).await.expect("echo");
+            auth
+        });
+
+        let cfg = AgentConfigFull {
+            api_url: format!("http://{}", addr),
+            ..Default::default()
+        };
+        let blocked = open_socket(&cfg, "t", "ws://203.0.113.9/ws", &HashMap::new()).await;
+        assert!(matches!(blocked, Err(HelperError::SsrfBlocked { .. })));
+
+        let mut socket = open_socket(&cfg, "t", &format!("ws://{}/ws", addr), &HashMap::new())
+            .await
+            .expect("connect");
+        socket.send(Message::text("ping")).await.expect("send");
+        let echo = socket.next().await.expect("frame").expect("read");
+        assert_eq!(echo, Message::text("ping"));
+        assert_eq!(server.await.expect("server").as_deref(), Some("Bearer t"));
+    }
+}
@@ -152,6 +152,17 @@ it('shows a Retry button when the connection errors', () => {
   expect(screen.getByRole('button', { name: 'Retry' })).toBeInTheDocument();
 });
 
+it('shows onboarding instead of an error when the agent is not enrolled', () => {
+  setChatState({ connectionState: 'not-enrolled', connectionError: 'Install the agent' });
+  setWorkspaceState({ available: null });
+
+  render(<App />);
+
+  expect(screen.getByText("This device isn't set up with Breeze yet.")).toBeInTheDocument();
+  expect(screen.getByRole('button', { name: 'Check again' })).toBeInTheDocument();
+  expect(screen.queryByRole('button', { name: 'Retry' })).not.toBeInTheDocument();
+});
+
 it('shows the username prompt when no username is set yet', () => {
   setChatState({ connectionState: 'connected', username: null });
   setWorkspaceState({ available: true });
@@ -1,4 +1,5 @@
 import { useEffect, useState } from 'react';
+import { emit } from '@tauri-apps/api/event';
 import { useChatStore } from './stores/chatStore';
 import { useWorkspaceStore } from './stores/workspaceStore';
 import AppShell from './components/shell/AppShell';
@@ -56,6 +57,11 @@ export default function App() {
     initialize();
   }, [initialize]);
 
+  // Mirror the connection state into the tray tooltip.
+  useEffect(() => {
+    emit('helper-connection-state', connectionState).catch(() => {});
+  }, [connectionState]);
+
   // Probe the workspace files capability once the connection is ready.
   // 404/401 leaves available=false and the Files affordance hidden.
   useEffect(() => {
@@ -83,6 +89,20 @@ export default function App() {
     );
   }
 
+  if (connectionState === 'not-enrolled') {
+    return (
+      <div className="helper-container helper-center">
+        <span className="helper-spinner" />
+        <p>This device isn't set up with Breeze yet.</p>
+        <p>{connectionError}</p>
+        <p>Waiting for enrollment…</p>
+        <button onClick={initialize} className="helper-btn">
+          Check again
+        </button>
+      </div>
+    );
+  }
+
   if (connectionState === 'error') {
     return (
       <div className="helper-container helper-center">
@@ -14,6 +14,7 @@ export interface AgentConfig {
   has_mtls?: boolean;
   os_username?: string;
   helper_version?: string;
+  cert_expires_at?: string | null;
 }
 
 // ---------------------------------------------------------------------------
@@ -52,9 +53,90 @@ export async function getTauriInvoke(): Promise<
 
 export interface HelperFetchResponse {
   status: number;
+  /**
+   * Lowercase names. A repeated header is joined with `, `; `set-cookie`
+   * values are joined with `\n`.
+   */
   headers: Record<string, string>;
+  /** Where the response came from, after any redirects. */
+  final_url: string;
   body: string;
   stream_id: string | null;
+  /** Set for `download_to_path` requests, whose `body` is empty. */
+  bytes_written?: number | null;
+  /** The `Idempotency-Key` sent, for `idempotency: true` requests. */
+  idempotency_key?: string | null;
+}
+
+/** Payload of `helper-fetch-progress`, emitted at most ~10 times a second. */
+export interface HelperFetchProgress {
+  /** Set for streamed requests. */
+  stream_id: string | null;
+  /** Set for `download_to_path` requests. */
+  path: string | null;
+  bytes_so_far: number;
+  /** From `Content-Length` (or `Content-Range`); null when chunked. */
+  total_bytes: number | null;
+  done: boolean;
+}
+
+/** Payload of `helper-ws-message` (matches Rust `WsMessageEvent`). */
+export interface HelperWsMessage {
+  ws_id: string;
+  /** Text frame as-is, or base64 when `binary`. Null on the terminal event. */
+  data: string | null;
+  binary: boolean;
+  done: boolean;
+  close_code: number | null;
+  close_reason: string | null;
+  error: string | null;
+}
+
+/** Rejection payload of helper_fetch (matches Rust `HelperError`). */
+export type HelperErrorKind =
+  | 'not_enrolled'
+  | 'config_unreadable'
+  | 'config_missing'
+  | 'config_parse'
+  | 'ssrf_blocked'
+  | 'timeout'
+  | 'connect_timeout'
+  | 'tls'
+  | 'network'
+  | 'http_status'
+  | 'internal';
+
+export interface HelperError {
+  kind: HelperErrorKind;
+  message: string;
+  /** Only set for `http_status`. */
+  status?: number;
+}
+
+/** Error thrown for a failed helper_fetch, carrying the Rust error class. */
+export class HelperFetchError extends Error {
+  readonly kind: HelperErrorKind;
+  readonly status?: number;
+
+  constructor(error: HelperError) {
+    super(error.message);
+    this.name = 'HelperFetchError';
+    this.kind = error.kind;
+    this.status = error.status;
+  }
+}
+
+/** Wrap a structured helper_fetch rejection so `instanceof Error` callers keep working. */
+export function toHelperFetchError(err: unknown): unknown {
+  if (
+    err &&
+    typeof err === 'object' &&
+    typeof (err as HelperError).kind === 'string' &&
+    typeof (err as HelperError).message === 'string'
+  ) {
+    return new HelperFetchError(err as HelperError);
+  }
+  return err;
 }
 
 export function requireDevBearerToken(config: AgentConfig): string {
@@ -75,8 +157,18 @@ export function requireDevBearerToken(config: AgentConfig): string {
 export async function helperRequest(
   config: AgentConfig,
   url: string,
-  options: { method?: string; headers?: Record<string, string>; body?: string },
-): Promise<{ ok: boolean; status: number; body: string }> {
+  options: {
+    method?: string;
+    headers?: Record<string, string>;
+    body?: string;
+    /** Share an identical GET already in flight instead of sending another. */
+    coalesce?: boolean;
+    /** Set `agent_id` in the JSON object `body` from the agent config. */
+    injectAgentId?: boolean;
+    /** POST only: send an `Idempotency-Key` so the server can drop duplicates. */
+    idempotency?: boolean;
+  },
+): Promise<{ ok: boolean; status: number; body: string; idempotencyKey?: string }> {
   const invoke = await getTauriInvoke();
 
   if (invoke) {
@@ -87,26 +179,56 @@ export async function helperRequest(
         headers: options.headers ?? {},
         body: options.body,
         stream: false,
+        coalesce: options.coalesce,
+        inject_agent_id: options.injectAgentId,
+        idempotency: options.idempotency,
       },
+    }).catch((err) => {
+      throw toHelperFetchError(err);
     })) as HelperFetchResponse;
 
     return {
       ok: resp.status >= 200 && resp.status < 300,
       status: resp.status,
       body: resp.body,
+      idempotencyKey: resp.idempotency_key ?? undefined,
     };
   }
 
   // Dev fallback: use native fetch
+  const body = options.injectAgentId
+    ? JSON.stringify({ ...JSON.parse(options.body ?? 'null'), agent_id: config.agent_id })
+    : options.body;
+  const idempotencyKey = options.idempotency ? crypto.randomUUID() : undefined;
   const res = await fetch(url, {
     method: options.method ?? 'GET',
     headers: {
       Authorization: `Bearer ${requireDevBearerToken(config)}`,
+      ...(idempotencyKey ? { 'Idempotency-Key': idempotencyKey } : {}),
       ...(options.headers ?? {}),
     },
-    body: options.body,
+    body,
   });
 
-  const body = await res.text();
-  return { ok: res.ok, status: res.status, body };
+  return { ok: res.ok, status: res.status, body: await res.text(), idempotencyKey };
+}
+
+/** Result of `validate_request_url` (matches Rust `UrlValidation`). */
+export interface UrlValidation {
+  allowed: boolean;
+  /** Only requests to the API origin carry the bearer token. */
+  will_attach_auth: boolean;
+  reason: string | null;
+}
+
+/**
+ * Ask the helper whether `helper_fetch` would accept `url`, without sending
+ * anything. Null outside Tauri, where the dev fallback checks nothing.
+ */
+export async function validateRequestUrl(url: string): Promise<UrlValidation | null> {
+  const invoke = await getTauriInvoke();
+  if (!invoke) return null;
+  return (await invoke('validate_request_url', { url }).catch((err) => {
+    throw toHelperFetchError(err);
+  })) as UrlValidation;
 }
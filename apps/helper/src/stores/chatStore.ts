@@ -1,8 +1,10 @@
 import { create } from 'zustand';
 import {
   getTauriInvoke,
+  HelperFetchError,
   helperRequest,
   requireDevBearerToken,
+  toHelperFetchError,
   type AgentConfig,
   type HelperFetchResponse,
 } from '../lib/helperFetch';
@@ -41,6 +43,7 @@ type ConnectionState =
   | 'connecting'
   | 'waiting-for-token'
   | 'connected'
+  | 'not-enrolled'
   | 'error';
 
 export interface DeviceContext {
@@ -123,6 +126,18 @@ interface StreamChunkEvent {
   chunk: string | null;
   done: boolean;
   error: string | null;
+  /** Total body bytes, set on the terminal event (null if cancelled). */
+  bytes_received?: number | null;
+  /** Set instead of `chunk` when the request used `parse_sse: true`. */
+  event?: string | null;
+  data?: string | null;
+  id?: string | null;
+  /** Transient: the helper is reconnecting a `reconnect: true` SSE stream. */
+  reconnecting?: boolean;
+  /** HTTP status, on the terminal event (null if cancelled). */
+  status?: number | null;
+  /** HTTP trailers, on the terminal event of a completed stream. */
+  trailers?: Record<string, string> | null;
 }
 
 /**
@@ -135,7 +150,15 @@ interface StreamChunkEvent {
 async function helperStreamRequest(
   config: AgentConfig,
   url: string,
-  options: { method?: string; headers?: Record<string, string>; body?: string },
+  options: {
+    method?: string;
+    headers?: Record<string, string>;
+    body?: string;
+    /** Batch chunks into one event at most every N ms (Tauri path only). */
+    coalesceMs?: number;
+    /** Events queued before reading pauses for the UI (Tauri path only). */
+    streamQueueCapacity?: number;
+  },
   onChunk: (text: string) => void,
   onDone: (error?: string) => void,
 ): Promise<{
@@ -183,7 +206,11 @@ async function helperStreamRequest(
         headers: options.headers ?? {},
         body: options.body,
         stream: true,
+        coalesce_ms: options.coalesceMs,
+        stream_queue_capacity: options.streamQueueCapacity,
       },
+    }).catch((err) => {
+      throw toHelperFetchError(err);
     })) as HelperFetchResponse;
 
     const isOk = resp.status >= 200 && resp.status < 300;
@@ -205,6 +232,9 @@ async function helperStreamRequest(
       headers: resp.headers,
       cancel: () => {
         if (unlisten) { unlisten(); unlisten = null; }
+        // Stop the Rust reader too, otherwise it keeps draining the
+        // connection until the server closes it.
+        invoke('cancel_stream', { streamId }).catch(() => {});
       },
     };
   }
@@ -487,6 +517,9 @@ export async function processSSELines(
 
 const USERNAME_KEY = 'breeze-helper-username';
 
+/** Set while a `wait_for_enrollment` call is outstanding, so retries don't stack them. */
+let waitingForEnrollment = false;
+
 /** Max time to wait on a cold-start capabilities probe before creating a session. */
 const WORKSPACE_PROBE_TIMEOUT_MS = 3000;
 
@@ -553,7 +586,9 @@ export const useChatStore = create<ChatState>((set, get) => ({
 
       const invoke = await getTauriInvoke();
       if (invoke) {
-        config = (await invoke('read_agent_config')) as AgentConfig;
+        config = (await invoke('read_agent_config').catch((err) => {
+          throw toHelperFetchError(err);
+        })) as AgentConfig;
       } else {
         // Dev fallback: read from env or local config
         const apiUrl = (import.meta as unknown as { env?: Record<string, string> }).env?.VITE_API_URL;
@@ -620,6 +655,24 @@ export const useChatStore = create<ChatState>((set, get) => ({
       set({ agentConfig: config, connectionState: 'connected', username });
     } catch (err) {
       const message = err instanceof Error ? err.message : String(err);
+      // No agent config yet is a setup step, not a failure — App shows
+      // onboarding for it instead of the error banner.
+      if (err instanceof HelperFetchError && err.kind === 'not_enrolled') {
+        set({ connectionState: 'not-enrolled', connectionError: message });
+        // The helper resolves this once the agent writes its config; connect
+        // then instead of asking the user to press "Check again".
+        const invoke = await getTauriInvoke();
+        if (invoke && !waitingForEnrollment) {
+          waitingForEnrollment = true;
+          invoke('wait_for_enrollment')
+            .then(() => get().initialize())
+            .catch((waitErr) => console.warn('[Helper] Still waiting for enrollment:', waitErr))
+            .finally(() => {
+              waitingForEnrollment = false;
+            });
+        }
+        return;
+      }
       console.error('[Helper] Initialize failed:', message);
       set({ connectionState: 'error', connectionError: message });
     }
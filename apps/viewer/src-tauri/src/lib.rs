@@ -1,14 +1,25 @@
-use std::collections::HashMap;
-#[cfg(any(target_os = "linux", test))]
-use std::path::{Path, PathBuf};
-use std::sync::{Mutex, MutexGuard};
+use std::collections::{BTreeSet, HashMap, HashSet};
+#[cfg(any(target_os = "linux", target_os = "windows", test))]
+use std::path::Path;
+use std::path::PathBuf;
+use hmac::{Hmac, KeyInit, Mac};
+use parking_lot::Mutex;
+use sha2::Sha256;
+use tauri::menu::{Menu, MenuItem};
+use tauri::tray::TrayIconBuilder;
 use tauri::{Emitter, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
 use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_updater::UpdaterExt;
 use url::Url;
 
+mod logging;
+
 const MAX_DEEP_LINK_BYTES: usize = 4096;
-const MAX_SESSION_WINDOWS: usize = 16;
+/// Session windows allowed at once unless `BREEZE_VIEWER_MAX_SESSIONS` says
+/// otherwise. Guards against a deep-link loop spawning windows without bound.
+const DEFAULT_MAX_SESSIONS: usize = 12;
+/// Upper bound for `BREEZE_VIEWER_MAX_SESSIONS`.
+const MAX_SESSIONS_CEILING: usize = 32;
 const MAX_ID_PARAM_BYTES: usize = 128;
 const MAX_CODE_PARAM_BYTES: usize = 512;
 const MAX_API_PARAM_BYTES: usize = 2048;
@@ -19,7 +30,7 @@ const IDLE_CARD_DELAY: std::time::Duration = std::time::Duration::from_millis(80
 
 /// Register this app bundle with macOS Launch Services so the `breeze://`
 /// URL scheme always resolves to the current install location (not a stale
-/// DMG mount path). This is a no-op on non-macOS platforms.
+/// DMG mount path). Linux and Windows have their own variants below.
 #[cfg(target_os = "macos")]
 fn register_url_scheme() -> Result<(), String> {
     let exe = std::env::current_exe().map_err(|e| format!("cannot resolve own path: {e}"))?;
@@ -274,34 +285,137 @@ fn scheme_association_is_ours(entry_name: &str) -> bool {
 fn run_best_effort(program: &str, args: &[&std::ffi::OsStr]) {
     match std::process::Command::new(program).args(args).output() {
         Ok(output) if !output.status.success() => {
-            eprintln!(
-                "{} exited with {}: {}",
-                program,
-                output.status,
-                String::from_utf8_lossy(&output.stderr).trim()
+            tracing::warn!(
+                status = %output.status,
+                stderr = %String::from_utf8_lossy(&output.stderr).trim(),
+                "{program} failed"
             );
         }
         // Expected on minimal desktops — kept distinct from real failures so
         // the benign case doesn't train anyone to ignore this log line.
         Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-            eprintln!("{program} is not installed — breeze:// association not refreshed");
+            tracing::warn!("{program} is not installed — breeze:// association not refreshed");
         }
-        Err(err) => eprintln!("failed to run {program}: {err}"),
+        Err(err) => tracing::warn!(error = %err, "failed to run {program}"),
         Ok(_) => {}
     }
 }
 
-/// Windows (and every other non-macOS, non-Linux target) self-registers nothing
-/// at runtime. The `breeze` scheme declared in `tauri.conf.json`
-/// (`plugins.deep-link.desktop.schemes`) is baked into the bundler's stock WiX
-/// template, which writes `HKLM\Software\Classes\breeze` at MSI install time.
-/// The source of truth is that config array — not this file, and not
-/// tauri-plugin-deep-link's unused HKCU `register()`.
-#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+// ── Windows `breeze://` registration ─────────────────────────────────────
+// Same split as Linux: the command/parsing helpers are pure and compiled under
+// `cfg(test)` everywhere; only `register_url_scheme` itself touches `reg.exe`.
+
+/// Per-user class key for the scheme. HKCU wins over the MSI's
+/// `HKLM\Software\Classes\breeze` in the merged HKCR view, so this follows the
+/// exe when the install is moved or an upgrade changes its path.
+#[cfg(any(target_os = "windows", test))]
+const WINDOWS_SCHEME_KEY: &str = r"HKCU\Software\Classes\breeze";
+
+/// The `shell\open\command` value for `exe`: `"<exe>" "%1"`.
+///
+/// `None` for paths Windows itself can't contain (`"`) or that would break the
+/// value (control characters); registration is skipped and the reason logged.
+#[cfg(any(target_os = "windows", test))]
+fn windows_open_command(exe: &Path) -> Option<String> {
+    if !exe.is_absolute() {
+        return None;
+    }
+    let exe = exe.to_str()?;
+    if exe.chars().any(|c| c == '"' || c.is_control()) {
+        return None;
+    }
+    Some(format!("\"{exe}\" \"%1\""))
+}
+
+/// `reg.exe` argument lists that (re)write the per-user protocol handler.
+#[cfg(any(target_os = "windows", test))]
+fn windows_registration_commands(open_command: &str) -> Vec<Vec<String>> {
+    let command_key = format!(r"{WINDOWS_SCHEME_KEY}\shell\open\command");
+    [
+        vec!["add", WINDOWS_SCHEME_KEY, "/ve", "/d", "URL:Breeze Protocol", "/f"],
+        vec!["add", WINDOWS_SCHEME_KEY, "/v", "URL Protocol", "/d", "", "/f"],
+        vec!["add", &command_key, "/ve", "/d", open_command, "/f"],
+    ]
+    .into_iter()
+    .map(|args| args.into_iter().map(str::to_string).collect())
+    .collect()
+}
+
+/// The default value from `reg query <key> /ve` output, e.g.
+/// `    (Default)    REG_SZ    "C:\...\breeze-viewer.exe" "%1"`.
+#[cfg(any(target_os = "windows", test))]
+fn parse_reg_default_value(stdout: &str) -> Option<String> {
+    stdout.lines().find_map(|line| {
+        let (_, value) = line.split_once("REG_SZ")?;
+        Some(value.trim().to_string())
+    })
+}
+
+/// Register this install as the `breeze://` handler for the current user.
+///
+/// The MSI writes the scheme under HKLM at install time, but that entry goes
+/// stale when the app is moved or a per-user install lands elsewhere, and the
+/// deep link then opens nothing. Runs on every launch; the keys are rewritten
+/// only when the open command doesn't already point at `current_exe()`.
+#[cfg(target_os = "windows")]
+fn register_url_scheme() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("cannot resolve own path: {e}"))?;
+    let open_command = windows_open_command(&exe)
+        .ok_or_else(|| format!("exe path cannot be registered: {}", exe.display()))?;
+
+    let command_key = format!(r"{WINDOWS_SCHEME_KEY}\shell\open\command");
+    let current = run_reg(&["query", &command_key, "/ve"])
+        .ok()
+        .and_then(|stdout| parse_reg_default_value(&stdout));
+    if current.as_deref() == Some(open_command.as_str()) {
+        return Ok(());
+    }
+
+    for args in windows_registration_commands(&open_command) {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        run_reg(&args)?;
+    }
+    Ok(())
+}
+
+/// Run `reg.exe` without flashing a console window; stdout on success, the
+/// captured stderr on failure.
+#[cfg(target_os = "windows")]
+fn run_reg(args: &[&str]) -> Result<String, String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let output = std::process::Command::new("reg")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("failed to run reg.exe: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "reg {} exited with {}: {}",
+            args.first().copied().unwrap_or_default(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Every other target self-registers nothing at runtime.
+#[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
 fn register_url_scheme() -> Result<(), String> {
     Ok(())
 }
 
+// ── Shared state ─────────────────────────────────────────────────────────
+// All managed state sits behind `parking_lot::Mutex`, which has no poisoning.
+// Consistency guarantee: every critical section is a single map/slot
+// operation (insert, remove, retain, clone, read) that either completes or
+// never starts, so a panic elsewhere can't leave a half-applied update behind.
+// Keep it that way — no focus/show/build or emits (which can re-enter)
+// while a guard is held. A panic in a command surfaces as a failed invoke
+// rather than being logged and papered over on the next lock.
+
 /// Per-window pending deep link URLs. Key = window label, value = deep link URL.
 struct DeepLinkState(Mutex<HashMap<String, String>>);
 
@@ -320,8 +434,95 @@ struct SessionMap(Mutex<HashMap<String, SessionEntry>>);
 /// Used to focus an existing window when the same device is connected again.
 struct DeviceMap(Mutex<HashMap<String, String>>);
 
+/// Maps window_label → the link that opened the window, from creation until
+/// the window is destroyed. Routing dedups on it through the gap between the
+/// frontend clearing its pending link and registering the session, and
+/// compares it against a focusing link's `view_only`.
+struct WindowLinks(Mutex<HashMap<String, DeepLinkParams>>);
+
+/// Deep links received before `setup()` has managed the state routing needs.
+/// Managed on the builder, so it exists before any plugin callback can fire.
+struct EarlyDeepLinks(Mutex<EarlyDeepLinkQueue>);
+
+/// Most deep links held while setup is still running; the oldest is dropped
+/// beyond this, since the newest is the one the tech just clicked.
+const MAX_EARLY_DEEP_LINKS: usize = 8;
+
+#[derive(Debug, Default)]
+struct EarlyDeepLinkQueue {
+    ready: bool,
+    pending: std::collections::VecDeque<String>,
+}
+
+impl EarlyDeepLinkQueue {
+    /// Hand `url` back for routing once state is ready; before that, buffer
+    /// it and return `None`.
+    fn offer(&mut self, url: String) -> Option<String> {
+        if self.ready {
+            return Some(url);
+        }
+        if self.pending.len() >= MAX_EARLY_DEEP_LINKS {
+            self.pending.pop_front();
+            tracing::warn!("early deep link queue full — dropped the oldest link");
+        }
+        self.pending.push_back(url);
+        None
+    }
+
+    /// Mark state ready and take everything buffered so far, oldest first.
+    fn drain(&mut self) -> Vec<String> {
+        self.ready = true;
+        self.pending.drain(..).collect()
+    }
+}
+
+/// Concurrent session window cap, resolved once at startup.
+struct SessionLimit(usize);
+
+/// `BREEZE_VIEWER_MAX_SESSIONS` as a window cap. Unset, unparseable, or zero
+/// means the default; larger values are clamped to `MAX_SESSIONS_CEILING`.
+fn max_sessions_from_env(value: Option<&str>) -> usize {
+    match value.and_then(|v| v.trim().parse::<usize>().ok()) {
+        Some(0) | None => DEFAULT_MAX_SESSIONS,
+        Some(n) => n.min(MAX_SESSIONS_CEILING),
+    }
+}
+
+/// Relay regions the backend routes sessions through. A link naming any other
+/// `relay` is rejected rather than steering the session somewhere unknown.
+const RELAY_REGIONS: [&str; 3] = ["us", "eu", "ap"];
+const DEFAULT_RELAY_REGION: &str = "us";
+
+/// Relay region for links that don't name one, resolved once at startup.
+struct HomeRelayRegion(String);
+
+/// `BREEZE_VIEWER_RELAY_REGION` as the home region; anything outside
+/// `RELAY_REGIONS` falls back to the default.
+fn relay_region_from_env(value: Option<&str>) -> String {
+    value
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|region| RELAY_REGIONS.contains(&region.as_str()))
+        .unwrap_or_else(|| DEFAULT_RELAY_REGION.to_string())
+}
+
 /// Monotonic counter for unique window labels.
-struct WindowCounter(Mutex<u32>);
+/// `u64` so an always-on viewer can never realistically wrap it; labels are
+/// still checked for reuse in `next_session_label`.
+struct WindowCounter(Mutex<u64>);
+
+/// Advance `counter` to the next `session-N` label that `taken` doesn't claim
+/// (a live window, or a stale `DeepLinkState` entry). Skips 0 on wrap so the
+/// label space stays `1..`.
+fn next_session_label(counter: &mut u64, taken: impl Fn(&str) -> bool) -> String {
+    loop {
+        *counter = counter.wrapping_add(1).max(1);
+        let label = format!("session-{}", counter);
+        if !taken(&label) {
+            return label;
+        }
+        tracing::warn!(label = %label, "window label is still in use, skipping");
+    }
+}
 
 /// A downloaded-but-not-yet-applied update, awaiting the user's choice in the
 /// `Ready` prompt. The `Update` handle is retained because `install()` is a
@@ -338,17 +539,20 @@ struct SchemeRegistration(Mutex<Option<String>>);
 
 #[tauri::command]
 fn get_scheme_registration_error(state: tauri::State<SchemeRegistration>) -> Option<String> {
-    lock_or_recover(&state.0, "scheme_registration").clone()
+    state.0.lock().clone()
 }
 
-fn lock_or_recover<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
-    match mutex.lock() {
-        Ok(guard) => guard,
-        Err(poisoned) => {
-            eprintln!("Recovering from poisoned mutex: {}", name);
-            poisoned.into_inner()
-        }
-    }
+const DEFAULT_RECENT_LOG_LINES: usize = 200;
+const MAX_RECENT_LOG_LINES: usize = 2000;
+
+/// The tail of the viewer log (default 200 lines, capped at 2000) for a tech
+/// to paste into a support ticket. Links in it are already redacted.
+#[tauri::command]
+fn get_recent_logs(lines: Option<usize>) -> Result<String, String> {
+    let lines = lines
+        .unwrap_or(DEFAULT_RECENT_LOG_LINES)
+        .min(MAX_RECENT_LOG_LINES);
+    Ok(logging::recent_lines(lines)?.join("\n"))
 }
 
 fn is_localhost(host: &str) -> bool {
@@ -383,24 +587,48 @@ fn require_param(parsed: &Url, name: &str, max_bytes: usize) -> Result<String, S
     Ok(value)
 }
 
+/// Endpoints a link may name, as `breeze://<endpoint>` or `breeze:<endpoint>`.
+/// Empty is the bare `breeze://?session=…` form, which connects.
+const DEEP_LINK_ENDPOINTS: [&str; 3] = ["", "connect", "vnc"];
+
+/// Whether `arg` is a `breeze:` link: exactly that scheme, not one that merely
+/// starts with it such as `breezemalware:` or `breeze-evil:`. Case-sensitive,
+/// like the lowercase scheme the viewer registers.
+fn is_breeze_link(arg: &str) -> bool {
+    arg.split_once(':')
+        .is_some_and(|(scheme, _)| scheme == "breeze")
+}
+
+/// Parse `url` as a `breeze:` link to one of `DEEP_LINK_ENDPOINTS`, returned
+/// as `https://breeze/<endpoint>?<query>` so callers see the same shape
+/// whichever form arrived.
 fn parse_breeze_deep_link(url: &str) -> Result<Url, String> {
     let trimmed = url.trim();
     if trimmed.is_empty() || trimmed.len() > MAX_DEEP_LINK_BYTES {
         return Err("deep link is empty or too large".to_string());
     }
 
-    let normalized = if let Some(rest) = trimmed.strip_prefix("breeze://") {
-        format!("https://breeze/{rest}")
-    } else if let Some(rest) = trimmed.strip_prefix("breeze:") {
-        format!("https://breeze/{rest}")
-    } else {
+    // Checked on the raw text: `Url` would lowercase `BREEZE:` into a match.
+    if !is_breeze_link(trimmed) {
         return Err("deep link must use the breeze scheme".to_string());
-    };
-
-    let parsed = Url::parse(&normalized).map_err(|_| "deep link is not a valid URL".to_string())?;
-    if parsed.host_str() != Some("breeze") {
+    }
+    let link = Url::parse(trimmed).map_err(|_| "deep link is not a valid URL".to_string())?;
+    if !link.username().is_empty() || link.password().is_some() || link.port().is_some() {
         return Err("deep link host is invalid".to_string());
     }
+    // `breeze://connect` names the endpoint as the host, `breeze:connect` as
+    // the path; anything after it (`breeze://evil.example/../connect`) is
+    // part of the endpoint and fails the check.
+    let endpoint = format!("{}{}", link.host_str().unwrap_or_default(), link.path());
+    let endpoint = endpoint.trim_matches('/');
+    if !DEEP_LINK_ENDPOINTS.contains(&endpoint) {
+        return Err("deep link path is not supported".to_string());
+    }
+
+    let mut parsed = Url::parse(&format!("https://breeze/{endpoint}"))
+        .map_err(|_| "deep link is not a valid URL".to_string())?;
+    parsed.set_query(link.query());
+    parsed.set_fragment(link.fragment());
     Ok(parsed)
 }
 
@@ -435,12 +663,12 @@ fn validate_deep_link(url: &str) -> Result<String, String> {
 /// owned copy so the caller can move it across the thread hop that defers window
 /// creation off the (possibly main-thread) single-instance callback (issue #1409).
 fn first_deep_link_arg(argv: &[String]) -> Option<String> {
-    argv.iter().find(|arg| arg.starts_with("breeze:")).cloned()
+    argv.iter().find(|arg| is_breeze_link(arg)).cloned()
 }
 
 fn active_session_window_count(app: &tauri::AppHandle) -> usize {
     let counter = app.state::<WindowCounter>();
-    let n = *lock_or_recover(&counter.0, "window_counter");
+    let n = *counter.0.lock();
     (1..=n)
         .filter(|i| {
             let label = format!("session-{}", i);
@@ -449,46 +677,203 @@ fn active_session_window_count(app: &tauri::AppHandle) -> usize {
         .count()
 }
 
-/// Extract the `session=` query parameter from a breeze:// deep link URL.
-fn extract_session_id(url: &str) -> Option<String> {
-    let query_start = match url.find('?') {
-        Some(i) => i,
-        None => {
-            eprintln!("Deep link missing query string");
-            return None;
+/// A validated breeze:// deep link and its query parameters.
+///
+/// `session` values are pulled out because routing dedups on them — a link
+/// may carry several (`?session=a&session=b`) to open many machines at once.
+/// Every other parameter (`code`, `api`, `device`, and optional ones such as
+/// `view_only`, `relay`, or `host_label`) lands in `extra`, percent-decoded.
+/// The first occurrence of a repeated key wins, matching `URLSearchParams.get`
+/// in the frontend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DeepLinkParams {
+    url: String,
+    /// Distinct, non-empty session ids in link order.
+    sessions: Vec<String>,
+    extra: HashMap<String, String>,
+}
+
+/// Parameters that describe one machine, so they can't be shared by the
+/// per-session links a multi-session link is split into.
+const SINGLE_MACHINE_PARAMS: [&str; 2] = ["device", "host_label"];
+
+impl DeepLinkParams {
+    fn parse(url: &str) -> Result<Self, String> {
+        let url = validate_deep_link(url)?;
+        let parsed = parse_breeze_deep_link(&url)?;
+        let mut sessions: Vec<String> = Vec::new();
+        let mut extra = HashMap::new();
+        for (key, value) in parsed.query_pairs() {
+            if key == "session" {
+                if !value.is_empty() && !sessions.iter().any(|id| *id == value) {
+                    sessions.push(value.into_owned());
+                }
+            } else {
+                extra
+                    .entry(key.into_owned())
+                    .or_insert_with(|| value.into_owned());
+            }
         }
-    };
-    let query = &url[query_start + 1..];
-    for pair in query.split('&') {
-        if let Some(value) = pair.strip_prefix("session=") {
-            let end = value.find('&').unwrap_or(value.len());
-            let id = &value[..end];
-            if !id.is_empty() {
-                return Some(id.to_string());
+        if let Some(relay) = extra.get("relay").filter(|relay| !relay.is_empty()) {
+            if !RELAY_REGIONS.contains(&relay.as_str()) {
+                return Err(format!("unknown relay region: {}", relay));
             }
-            eprintln!("Deep link has empty session parameter");
-            return None;
         }
+        Ok(Self {
+            url,
+            sessions,
+            extra,
+        })
     }
-    eprintln!("Deep link missing session parameter");
-    None
-}
 
-/// Extract the `device=` query parameter from a breeze:// deep link URL.
-fn extract_device_id(url: &str) -> Option<String> {
-    let query_start = url.find('?')?;
-    let query = &url[query_start + 1..];
-    for pair in query.split('&') {
-        if let Some(value) = pair.strip_prefix("device=") {
-            let end = value.find('&').unwrap_or(value.len());
-            let id = &value[..end];
-            if !id.is_empty() {
-                return Some(id.to_string());
+    /// Build the link for a session started in-app, as if the equivalent
+    /// `breeze://connect` link had been clicked. Values get the same limits
+    /// `validate_deep_link` applies.
+    fn for_session(
+        session_id: &str,
+        code: &str,
+        api: &str,
+        host_label: Option<&str>,
+        view_only: bool,
+    ) -> Result<Self, String> {
+        for (name, value, max_bytes) in [
+            ("session", session_id, MAX_ID_PARAM_BYTES),
+            ("code", code, MAX_CODE_PARAM_BYTES),
+            ("api", api, MAX_API_PARAM_BYTES),
+        ] {
+            if value.is_empty() || value.len() > max_bytes {
+                return Err(format!("{name} parameter is empty or too large"));
+            }
+        }
+        validate_api_url(api)?;
+
+        let mut extra = HashMap::from([
+            ("code".to_string(), code.to_string()),
+            ("api".to_string(), api.to_string()),
+        ]);
+        if let Some(host) = host_label.filter(|host| !host.is_empty()) {
+            extra.insert("host_label".to_string(), host.to_string());
+        }
+        if view_only {
+            extra.insert("view_only".to_string(), "1".to_string());
+        }
+
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("session", session_id);
+        // Fixed order so the same session always yields the same URL.
+        for key in ["code", "api", "host_label", "view_only"] {
+            if let Some(value) = extra.get(key) {
+                query.append_pair(key, value);
             }
+        }
+        let url = format!("breeze://connect?{}", query.finish());
+        if url.len() > MAX_DEEP_LINK_BYTES {
+            return Err("deep link is empty or too large".to_string());
+        }
+        Ok(Self {
+            url,
+            sessions: vec![session_id.to_string()],
+            extra,
+        })
+    }
+
+    /// The session the frontend connects to: the first `session` value.
+    fn session(&self) -> Option<&str> {
+        self.sessions.first().map(String::as_str)
+    }
+
+    /// One link per session id. A single-session link comes back unchanged;
+    /// for several, each copy keeps the shared parameters and drops
+    /// `SINGLE_MACHINE_PARAMS`. A copy that fails to re-validate is logged
+    /// and skipped.
+    fn split_sessions(&self) -> Vec<DeepLinkParams> {
+        if self.sessions.len() <= 1 {
+            return vec![self.clone()];
+        }
+        self.sessions
+            .iter()
+            .filter_map(|id| {
+                self.retarget(id, &SINGLE_MACHINE_PARAMS)
+                    .map_err(|err| {
+                        tracing::warn!(session_id = %id, error = %err, "dropped session from deep link")
+                    })
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// This link pointed at `new_session_id` instead, for a window whose
+    /// session was re-established under a new id. `None` unless the link is
+    /// for `old_session_id`. Only for links already accepted: any signature
+    /// no longer matches.
+    fn with_session(&self, old_session_id: &str, new_session_id: &str) -> Option<DeepLinkParams> {
+        if self.session() != Some(old_session_id) {
             return None;
         }
+        self.retarget(new_session_id, &[])
+            .map_err(|err| {
+                tracing::warn!(session_id = %new_session_id, error = %err, "failed to relink session")
+            })
+            .ok()
+    }
+
+    /// This link for `session_id` alone, keeping every other parameter not
+    /// named in `drop`.
+    fn retarget(&self, session_id: &str, drop: &[&str]) -> Result<DeepLinkParams, String> {
+        let parsed = parse_breeze_deep_link(&self.url)?;
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        query.append_pair("session", session_id);
+        for (key, value) in parsed.query_pairs() {
+            if key != "session" && !drop.contains(&key.as_ref()) {
+                query.append_pair(&key, &value);
+            }
+        }
+        DeepLinkParams::parse(&format!("breeze:/{}?{}", parsed.path(), query.finish()))
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.extra
+            .get(name)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    }
+
+    fn device(&self) -> Option<&str> {
+        self.param("device")
+    }
+
+    /// Window title for the session, so a tech with several windows open can
+    /// tell them apart before the viewer connects: the `host_label` hint when
+    /// the link carries one, else the first 8 characters of the session id.
+    fn window_title(&self) -> String {
+        if let Some(host) = self.param("host_label") {
+            return session_window_title(host);
+        }
+        match self.session() {
+            Some(session) => {
+                let short: String = session.chars().take(8).collect();
+                session_window_title(&short)
+            }
+            None => DEFAULT_WINDOW_TITLE.to_string(),
+        }
+    }
+
+    /// Key the window's saved geometry is stored under: the host when the
+    /// link names one, else the session id.
+    fn geometry_key(&self) -> Option<&str> {
+        self.param("host_label").or(self.session())
+    }
+
+    /// `view_only=1` (or `true`) asks the viewer to suppress local input.
+    fn view_only(&self) -> bool {
+        matches!(self.param("view_only"), Some("1") | Some("true"))
+    }
+
+    /// Relay region to connect through: the link's `relay`, which `parse`
+    /// has already checked against `RELAY_REGIONS`, else `home`.
+    fn relay_region<'a>(&'a self, home: &'a str) -> &'a str {
+        self.param("relay").unwrap_or(home)
     }
-    None
 }
 
 /// Called by the frontend to poll for a pending deep link URL.
@@ -498,14 +883,33 @@ fn get_pending_deep_link(
     window: tauri::WebviewWindow,
     state: tauri::State<'_, DeepLinkState>,
 ) -> Option<String> {
-    let map = lock_or_recover(&state.0, "deep_link_state");
+    let map = state.0.lock();
     map.get(window.label()).cloned()
 }
 
+/// Called by the frontend once its `deep-link-received` listener is registered.
+/// Emits the calling window's pending URL straight away, so delivery no longer
+/// depends on guessing how long the webview takes to start. The URL stays
+/// pending until `clear_pending_deep_link`, leaving the poll as a fallback.
+#[tauri::command]
+fn frontend_ready(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, DeepLinkState>,
+) {
+    let label = window.label();
+    let url = state.0.lock().get(label).cloned();
+    if let Some(url) = url {
+        if let Err(err) = app.emit_to(label, "deep-link-received", url) {
+            tracing::warn!(label = %label, error = %err, "failed to emit deep-link-received");
+        }
+    }
+}
+
 /// Called by the frontend to clear the pending URL after it has been applied.
 #[tauri::command]
 fn clear_pending_deep_link(window: tauri::WebviewWindow, state: tauri::State<'_, DeepLinkState>) {
-    let mut map = lock_or_recover(&state.0, "deep_link_state");
+    let mut map = state.0.lock();
     map.remove(window.label());
 }
 
@@ -516,15 +920,21 @@ fn register_session(
     window: tauri::WebviewWindow,
     session_id: String,
     state: tauri::State<'_, SessionMap>,
+    activity: tauri::State<'_, SessionActivity>,
 ) {
-    let mut map = lock_or_recover(&state.0, "session_map");
-    map.insert(
+    tracing::info!(session_id = %session_id, label = %window.label(), "session registered");
+    state.0.lock().insert(
         session_id,
         SessionEntry {
             window_label: window.label().to_string(),
             hostname: None,
         },
     );
+    activity
+        .0
+        .lock()
+        .insert(window.label().to_string(), IdleEntry::now());
+    save_session_restore(window.app_handle());
 }
 
 /// Called by the frontend on disconnect (session no longer active).
@@ -533,11 +943,230 @@ fn unregister_session(
     window: tauri::WebviewWindow,
     sessions: tauri::State<'_, SessionMap>,
     devices: tauri::State<'_, DeviceMap>,
+    activity: tauri::State<'_, SessionActivity>,
+) {
+    tracing::info!(label = %window.label(), "session unregistered");
+    sessions
+        .0
+        .lock()
+        .retain(|_, entry| entry.window_label != window.label());
+    devices.0.lock().retain(|_, label| label != window.label());
+    activity.0.lock().remove(window.label());
+    save_session_restore(window.app_handle());
+}
+
+/// One row of `list_sessions`: a registered session and the window showing it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+struct SessionSummary {
+    session_id: String,
+    window_label: String,
+    title: String,
+    always_on_top: bool,
+}
+
+/// Join the session map with live window titles, ordered by window label.
+/// Sessions whose window is already gone (no title) are left out.
+fn session_summaries(
+    sessions: &HashMap<String, SessionEntry>,
+    title_of: impl Fn(&str) -> Option<String>,
+    always_on_top: impl Fn(&str) -> bool,
+) -> Vec<SessionSummary> {
+    let mut out: Vec<SessionSummary> = sessions
+        .iter()
+        .filter_map(|(session_id, entry)| {
+            Some(SessionSummary {
+                title: title_of(&entry.window_label)?,
+                session_id: session_id.clone(),
+                window_label: entry.window_label.clone(),
+                always_on_top: always_on_top(&entry.window_label),
+            })
+        })
+        .collect();
+    // Shorter labels first keeps `session-2` ahead of `session-10`.
+    out.sort_by(|a, b| {
+        (a.window_label.len(), &a.window_label, &a.session_id).cmp(&(
+            b.window_label.len(),
+            &b.window_label,
+            &b.session_id,
+        ))
+    });
+    out
+}
+
+/// Active sessions and the windows showing them, for a session switcher (the
+/// frontend raises one with `focus_session`) and for checking routing decisions.
+#[tauri::command]
+fn list_sessions(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, SessionMap>,
+) -> Vec<SessionSummary> {
+    // Snapshot and release the lock before touching windows.
+    let sessions = state.0.lock().clone();
+    session_summaries(
+        &sessions,
+        |label| app.get_webview_window(label)?.title().ok(),
+        |label| {
+            app.get_webview_window(label)
+                .and_then(|window| window.is_always_on_top().ok())
+                .unwrap_or(false)
+        },
+    )
+}
+
+/// Bring the window showing `session_id` to the front, restoring it if it was
+/// minimized: what the OS does when the user activates the window, for an
+/// in-app session list. Only registered sessions resolve.
+#[tauri::command]
+fn focus_session(
+    app: tauri::AppHandle,
+    session_id: String,
+    state: tauri::State<'_, SessionMap>,
+) -> Result<(), String> {
+    // Clone the label and drop the lock before touching the window.
+    let label = state
+        .0
+        .lock()
+        .get(&session_id)
+        .map(|entry| entry.window_label.clone());
+    let window = label
+        .and_then(|label| app.get_webview_window(&label))
+        .ok_or_else(|| format!("No open window for session {}", session_id))?;
+    window
+        .unminimize()
+        .and_then(|_| window.show())
+        .and_then(|_| window.set_focus())
+        .map_err(|e| format!("Failed to focus session {}: {}", session_id, e))?;
+    tracing::info!(session_id = %session_id, label = %window.label(), "focused session window");
+    Ok(())
+}
+
+/// Payload of `session-reconnected`, sent to the window whose session was
+/// re-established under a new id.
+#[derive(Debug, Clone, serde::Serialize)]
+struct SessionReconnected {
+    old_session_id: String,
+    new_session_id: String,
+}
+
+/// Move `old_session_id`'s entry (window and hostname) to `new_session_id`
+/// on behalf of the window `label`. Fails without changing anything when the
+/// old session isn't registered to that window or the new one belongs to
+/// another window.
+fn remap_session(
+    sessions: &mut HashMap<String, SessionEntry>,
+    label: &str,
+    old_session_id: &str,
+    new_session_id: &str,
+) -> Result<(), String> {
+    if sessions
+        .get(old_session_id)
+        .is_none_or(|entry| entry.window_label != label)
+    {
+        return Err(format!(
+            "Session {} is not registered to this window",
+            old_session_id
+        ));
+    }
+    if sessions
+        .get(new_session_id)
+        .is_some_and(|entry| entry.window_label != label)
+    {
+        return Err(format!(
+            "Session {} is already open in another window",
+            new_session_id
+        ));
+    }
+    if let Some(entry) = sessions.remove(old_session_id) {
+        sessions.insert(new_session_id.to_string(), entry);
+    }
+    Ok(())
+}
+
+/// Point the link `label` was opened with at `new_session_id`, in both
+/// `WindowLinks` and the restore store, so routing no longer matches the old
+/// id and a restore reopens the new one. Takes each lock in turn.
+fn relink_window(app: &tauri::AppHandle, label: &str, old_session_id: &str, new_session_id: &str) {
+    if let Some(windows) = app.try_state::<WindowLinks>() {
+        relink_window_link(&mut windows.0.lock(), label, old_session_id, new_session_id);
+    }
+    if let Some(store) = app.try_state::<SessionRestoreStore>() {
+        relink_restore_link(
+            &mut store.links.lock(),
+            label,
+            old_session_id,
+            new_session_id,
+        );
+    }
+}
+
+fn relink_window_link(
+    windows: &mut HashMap<String, DeepLinkParams>,
+    label: &str,
+    old_session_id: &str,
+    new_session_id: &str,
+) {
+    if let Some(link) = windows.get_mut(label) {
+        if let Some(relinked) = link.with_session(old_session_id, new_session_id) {
+            *link = relinked;
+        }
+    }
+}
+
+fn relink_restore_link(
+    links: &mut HashMap<String, String>,
+    label: &str,
+    old_session_id: &str,
+    new_session_id: &str,
 ) {
-    let mut session_map = lock_or_recover(&sessions.0, "session_map");
-    session_map.retain(|_, entry| entry.window_label != window.label());
-    let mut device_map = lock_or_recover(&devices.0, "device_map");
-    device_map.retain(|_, label| label != window.label());
+    if let Some(url) = links.get_mut(label) {
+        let relinked = DeepLinkParams::parse(url)
+            .ok()
+            .and_then(|link| link.with_session(old_session_id, new_session_id));
+        if let Some(relinked) = relinked {
+            *url = relinked.url;
+        }
+    }
+}
+
+/// For a session window that re-establishes its session under a new id, so
+/// routing, `focus_session` and `list_sessions` keep finding it. Only the
+/// window holding `old_session_id` may move it. DesktopViewer's own
+/// auto-reconnect reuses the session id and does not need this.
+#[tauri::command]
+fn replace_session(
+    window: tauri::WebviewWindow,
+    old_session_id: String,
+    new_session_id: String,
+    state: tauri::State<'_, SessionMap>,
+) -> Result<(), String> {
+    let label = window.label().to_string();
+    remap_session(
+        &mut state.0.lock(),
+        &label,
+        &old_session_id,
+        &new_session_id,
+    )?;
+    relink_window(
+        window.app_handle(),
+        &label,
+        &old_session_id,
+        &new_session_id,
+    );
+    tracing::info!(
+        old_session_id = %old_session_id,
+        new_session_id = %new_session_id,
+        label = %label,
+        "session reconnected"
+    );
+    save_session_restore(window.app_handle());
+    let reconnected = SessionReconnected {
+        old_session_id,
+        new_session_id,
+    };
+    if let Err(err) = window.emit_to(&label, "session-reconnected", reconnected) {
+        tracing::warn!(label = %label, error = %err, "failed to emit session-reconnected");
+    }
+    Ok(())
 }
 
 /// Called by DesktopViewer when the device id is known.
@@ -548,33 +1177,64 @@ fn register_device(
     device_id: String,
     state: tauri::State<'_, DeviceMap>,
 ) {
-    let mut map = lock_or_recover(&state.0, "device_map");
+    let mut map = state.0.lock();
     map.insert(device_id, window.label().to_string());
 }
 
+/// Title of the anchor window and of sessions with nothing better to show.
+const DEFAULT_WINDOW_TITLE: &str = "Breeze Remote Desktop";
+const MAX_TITLE_LABEL_CHARS: usize = 64;
+
+/// `Breeze — <label>`, with control characters dropped and the label capped so
+/// a hostile link or hostname can't produce an unreadable title.
+fn session_window_title(label: &str) -> String {
+    let label: String = label
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_TITLE_LABEL_CHARS)
+        .collect();
+    if label.is_empty() {
+        return DEFAULT_WINDOW_TITLE.to_string();
+    }
+    format!("Breeze — {}", label)
+}
+
+fn apply_session_title(window: &tauri::WebviewWindow, label: &str) {
+    let title = session_window_title(label);
+    if let Err(err) = window.set_title(&title) {
+        tracing::warn!(title = %title, error = %err, "failed to set window title");
+    }
+}
+
+/// Called by the frontend to retitle its window once it knows a better label
+/// than the deep link gave it (typically the hostname from the handshake).
+#[tauri::command]
+fn set_session_title(window: tauri::WebviewWindow, label: String) {
+    apply_session_title(&window, &label);
+}
+
 /// Called by DesktopViewer when the remote hostname is learned.
 /// Updates the SessionMap entry and sets the native window title.
 #[tauri::command]
 fn update_session_hostname(
-    app: tauri::AppHandle,
     window: tauri::WebviewWindow,
     hostname: String,
     state: tauri::State<'_, SessionMap>,
 ) {
     // Update the window title from Rust (more reliable than JS setTitle)
-    if let Some(win) = app.get_webview_window(window.label()) {
-        let title = format!("{} — Breeze Viewer", hostname);
-        if let Err(err) = win.set_title(&title) {
-            eprintln!("Failed to set window title to '{}': {}", title, err);
-        }
-    }
-    let mut map = lock_or_recover(&state.0, "session_map");
-    for entry in map.values_mut() {
-        if entry.window_label == window.label() {
-            entry.hostname = Some(hostname);
+    apply_session_title(&window, &hostname);
+    {
+        let mut map = state.0.lock();
+        let Some(entry) = map
+            .values_mut()
+            .find(|entry| entry.window_label == window.label())
+        else {
             return;
-        }
+        };
+        entry.hostname = Some(hostname);
     }
+    save_session_restore(window.app_handle());
 }
 
 /// "Restart & update": apply the stashed update now. On macOS/Linux this swaps
@@ -588,7 +1248,7 @@ fn apply_pending_update(
     pending: tauri::State<'_, PendingUpdate>,
 ) -> Result<(), String> {
     let taken = {
-        let mut slot = lock_or_recover(&pending.0, "pending_update");
+        let mut slot = pending.0.lock();
         slot.take()
     };
     let Some((update, bytes)) = taken else {
@@ -603,7 +1263,7 @@ fn apply_pending_update(
     emit_update_status(&app, UpdateStatus::Installing { version: version.clone() });
 
     if let Err(e) = update.install(bytes) {
-        eprintln!("Update install failed: {}", e);
+        tracing::error!(error = %e, "update install failed");
         emit_update_status(&app, UpdateStatus::Failed { version });
         return Err(e.to_string());
     }
@@ -614,6 +1274,9 @@ fn apply_pending_update(
     #[cfg(not(target_os = "windows"))]
     {
         emit_update_status(&app, UpdateStatus::Restarting { version });
+        // The restart tears down every session window; latch the exit first so
+        // that teardown leaves `sessions.json` for the relaunch to offer back.
+        EXIT_REQUESTED.store(true, std::sync::atomic::Ordering::SeqCst);
         app.restart()
     }
     #[cfg(target_os = "windows")]
@@ -628,7 +1291,7 @@ fn apply_pending_update(
 #[tauri::command]
 fn dismiss_pending_update(pending: tauri::State<'_, PendingUpdate>) -> Result<(), String> {
     let taken = {
-        let mut slot = lock_or_recover(&pending.0, "pending_update");
+        let mut slot = pending.0.lock();
         slot.take()
     };
     let Some((update, bytes)) = taken else {
@@ -637,7 +1300,7 @@ fn dismiss_pending_update(pending: tauri::State<'_, PendingUpdate>) -> Result<()
 
     #[cfg(not(target_os = "windows"))]
     if let Err(e) = update.install(bytes) {
-        eprintln!("Deferred update disk-swap failed: {}", e);
+        tracing::error!(error = %e, "deferred update disk-swap failed");
     }
     #[cfg(target_os = "windows")]
     drop((update, bytes));
@@ -645,17 +1308,130 @@ fn dismiss_pending_update(pending: tauri::State<'_, PendingUpdate>) -> Result<()
     Ok(())
 }
 
+/// The highest-numbered (most recently opened) live session window.
+fn most_recent_session_window(app: &tauri::AppHandle) -> Option<tauri::WebviewWindow> {
+    let n = *app.state::<WindowCounter>().0.lock();
+    (1..=n)
+        .rev()
+        .find_map(|i| app.get_webview_window(&format!("session-{}", i)))
+}
+
 /// Focus the highest-numbered session window, or do nothing if none exist.
 fn focus_any_session_window(app: &tauri::AppHandle) {
-    let counter = app.state::<WindowCounter>();
-    let n = *lock_or_recover(&counter.0, "window_counter");
-    for i in (1..=n).rev() {
-        let label = format!("session-{}", i);
-        if let Some(window) = app.get_webview_window(&label) {
-            let _ = window.set_focus();
-            return;
+    if let Some(window) = most_recent_session_window(app) {
+        let _ = window.set_focus();
+    }
+}
+
+/// Unminimize and raise every on-screen viewer window, session windows in
+/// opening order so the most recent one ends up focused. The hidden anchor
+/// window stays hidden. Used when the app is re-activated without a deep link
+/// (second launch, dock click), where focusing one window left a tech's other
+/// sessions buried.
+fn activate_all_windows(app: &tauri::AppHandle) {
+    let n = *app.state::<WindowCounter>().0.lock();
+    let labels =
+        std::iter::once("main".to_string()).chain((1..=n).map(|i| format!("session-{}", i)));
+    for label in labels {
+        let Some(window) = app.get_webview_window(&label) else {
+            continue;
+        };
+        // macOS reports minimized windows as not visible, so check both.
+        let on_screen = window.is_visible().unwrap_or(false);
+        let minimized = window.is_minimized().unwrap_or(false);
+        if !on_screen && !minimized {
+            continue;
+        }
+        if let Err(err) = window.unminimize() {
+            tracing::warn!(label = %label, error = %err, "failed to unminimize window");
         }
+        if let Err(err) = window.set_focus() {
+            tracing::warn!(label = %label, error = %err, "failed to focus window");
+        }
+    }
+}
+
+// ── Signed deep links ───────────────────────────────────────────────────
+// Anyone can craft `breeze://connect?session=<uuid>`, so a hostile page could
+// make the viewer open a session of its choosing. When a shared secret is
+// provisioned (`BREEZE_VIEWER_LINK_SECRET`), links must carry `exp` (unix
+// seconds) and `sig`: hex HMAC-SHA256 over `canonical_link_message`. Without a
+// secret nothing is checked, so unsigned links from older consoles still work.
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Secret for signed deep links; `None` leaves signing unenforced.
+struct LinkSigningKey(Option<Vec<u8>>);
+
+fn link_signing_key_from_env(value: Option<&str>) -> Option<Vec<u8>> {
+    value
+        .map(str::trim)
+        .filter(|secret| !secret.is_empty())
+        .map(|secret| secret.as_bytes().to_vec())
+}
+
+/// The signed message: the link path without slashes, a newline, then every
+/// query pair except `sig`, sorted by key then value and form-urlencoded.
+/// Sorting makes the signature independent of parameter order, which
+/// platforms don't reliably preserve.
+fn canonical_link_message(parsed: &Url) -> String {
+    let mut pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| key != "sig")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    pairs.sort();
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(pairs)
+        .finish();
+    format!("{}\n{}", parsed.path().trim_matches('/'), query)
+}
+
+/// Check `url`'s signature and expiry against `key`.
+fn verify_link_signature(url: &str, key: &[u8], now_unix: u64) -> Result<(), String> {
+    let parsed = parse_breeze_deep_link(url)?;
+    let param = |name: &str| {
+        parsed
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    let sig = param("sig").ok_or_else(|| "deep link is not signed".to_string())?;
+    let sig = hex::decode(sig).map_err(|_| "deep link signature is malformed".to_string())?;
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(canonical_link_message(&parsed).as_bytes());
+    mac.verify_slice(&sig)
+        .map_err(|_| "deep link signature is invalid".to_string())?;
+
+    let exp = param("exp")
+        .and_then(|exp| exp.parse::<u64>().ok())
+        .ok_or_else(|| "deep link has no valid expiry".to_string())?;
+    if exp <= now_unix {
+        return Err("deep link has expired".to_string());
+    }
+    Ok(())
+}
+
+/// Payload of `deep-link-rejected`, broadcast when an incoming link is refused.
+#[derive(Clone, serde::Serialize)]
+struct DeepLinkRejected {
+    reason: String,
+}
+
+/// Validate an incoming link and, when signing is configured, its signature.
+fn accept_deep_link(app: &tauri::AppHandle, url: &str) -> Result<DeepLinkParams, String> {
+    let link = DeepLinkParams::parse(url)?;
+    if let Some(key) = app.try_state::<LinkSigningKey>().and_then(|k| k.0.clone()) {
+        verify_link_signature(&link.url, &key, unix_now())?;
     }
+    Ok(link)
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
 }
 
 /// Route an incoming deep link URL to the appropriate window.
@@ -663,76 +1439,879 @@ fn focus_any_session_window(app: &tauri::AppHandle) {
 /// - If the session is already active in a window, focus that window.
 /// - Otherwise, create a new session window for it.
 fn route_deep_link(app: &tauri::AppHandle, url: String) {
-    let url = match validate_deep_link(&url) {
-        Ok(url) => url,
+    // Before setup finishes, the state below isn't managed yet; `app.state()`
+    // would panic. Buffer the link and let setup replay it.
+    let url = match app.try_state::<EarlyDeepLinks>() {
+        Some(early) => match early.0.lock().offer(url) {
+            Some(url) => url,
+            None => return,
+        },
+        None => url,
+    };
+    let link = match accept_deep_link(app, &url) {
+        Ok(link) => link,
         Err(err) => {
-            eprintln!("Rejected invalid deep link: {}", err);
+            tracing::warn!(url = %logging::redact_link(&url), reason = %err, "rejected deep link");
+            if let Err(e) = app.emit("deep-link-rejected", DeepLinkRejected { reason: err }) {
+                tracing::warn!(error = %e, "failed to emit deep-link-rejected");
+            }
             focus_any_session_window(app);
             return;
         }
     };
 
-    // Check device-id dedup first: if a window is already viewing this device,
-    // focus it and discard the new deep link entirely.
-    // Clone the label and drop the lock BEFORE calling set_focus(); on macOS
-    // set_focus pumps the AppKit run loop and can re-enter Tauri command
-    // handlers that also need this lock.
-    if let Some(device_id) = extract_device_id(&url) {
-        let existing_label = {
-            let devices = app.state::<DeviceMap>();
-            let map = lock_or_recover(&devices.0, "device_map");
-            map.get(&device_id).cloned()
-        }; // lock released here
-        if let Some(label) = existing_label {
-            if let Some(window) = app.get_webview_window(&label) {
-                if let Err(err) = window.set_focus() {
-                    eprintln!("Failed to focus existing device window {}: {}", label, err);
-                }
-                return;
-            }
-        }
+    tracing::info!(
+        url = %logging::redact_link(&link.url),
+        sessions = ?link.sessions,
+        "accepted deep link"
+    );
+    // A multi-session link opens (or focuses) one window per session.
+    for link in link.split_sessions() {
+        route_session_link(app, link);
     }
+}
 
-    // Fallback: dedup by session id (covers older web builds and edge cases).
-    if let Some(session_id) = extract_session_id(&url) {
+/// Labels in `sessions` whose window no longer exists, per `is_open`.
+fn closed_session_windows(
+    sessions: &HashMap<String, SessionEntry>,
+    is_open: impl Fn(&str) -> bool,
+) -> HashSet<String> {
+    sessions
+        .values()
+        .map(|entry| &entry.window_label)
+        .filter(|label| !is_open(label))
+        .cloned()
+        .collect()
+}
+
+/// Forget sessions (and their devices) mapped to windows that are gone. The
+/// `Destroyed` handler normally clears them, but an entry it missed — a window
+/// closed without the frontend calling `unregister_session` — would otherwise
+/// make routing "focus" a window that isn't there and drop every link for
+/// that session instead of opening a new window.
+fn purge_closed_session_windows(app: &tauri::AppHandle) {
+    // Snapshot and release the lock before looking windows up.
+    let sessions = app.state::<SessionMap>().0.lock().clone();
+    let closed = closed_session_windows(&sessions, |label| app.get_webview_window(label).is_some());
+    if closed.is_empty() {
+        return;
+    }
+    tracing::info!(labels = ?closed, "purging sessions mapped to closed windows");
+    app.state::<SessionMap>()
+        .0
+        .lock()
+        .retain(|_, entry| !closed.contains(&entry.window_label));
+    app.state::<DeviceMap>()
+        .0
+        .lock()
+        .retain(|_, label| !closed.contains(label));
+}
+
+/// The window already showing `session_id`: a registered session, or a
+/// window opened for it that has not connected yet. The second check keeps a
+/// re-sent link from opening duplicates before the first copy has connected.
+/// Only the session id is compared, so a copy of the link carrying different
+/// tracking parameters (`utm_*` and the like) still lands on the same window.
+fn window_for_session(app: &tauri::AppHandle, session_id: &str) -> Option<String> {
+    let registered = app
+        .state::<SessionMap>()
+        .0
+        .lock()
+        .get(session_id)
+        .map(|entry| entry.window_label.clone());
+    registered.or_else(|| window_opened_for(&app.state::<WindowLinks>().0.lock(), session_id))
+}
+
+/// The window whose opening link was for `session_id`. The lowest label wins
+/// so the answer does not depend on map order.
+fn window_opened_for(
+    windows: &HashMap<String, DeepLinkParams>,
+    session_id: &str,
+) -> Option<String> {
+    windows
+        .iter()
+        .filter(|(_, link)| link.session() == Some(session_id))
+        .map(|(label, _)| label)
+        .min()
+        .cloned()
+}
+
+/// Start a session from inside the app — e.g. a device picked from a list —
+/// without a round trip through the OS URL handler. Routed exactly like the
+/// equivalent deep link: an existing window for the session is focused,
+/// otherwise a new one opens. The viewer still needs the connect code and
+/// API origin to connect, so those are required alongside the session id.
+#[tauri::command]
+fn open_session(
+    app: tauri::AppHandle,
+    session_id: String,
+    code: String,
+    api: String,
+    host_label: Option<String>,
+    view_only: Option<bool>,
+) -> Result<(), String> {
+    let link = DeepLinkParams::for_session(
+        &session_id,
+        &code,
+        &api,
+        host_label.as_deref(),
+        view_only.unwrap_or(false),
+    )?;
+    // Hop off the command thread before building a window, the same way the
+    // single-instance and on_open_url handlers do (see issue #1409).
+    std::thread::spawn(move || {
+        let h = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            route_session_link(&h, link);
+        });
+    });
+    Ok(())
+}
+
+/// Route a single-session link: focus the window already showing its device
+/// or session, else open a new one. Returns whether a window for it is on
+/// screen afterwards.
+fn route_session_link(app: &tauri::AppHandle, link: DeepLinkParams) -> bool {
+    purge_closed_session_windows(app);
+    // Check device-id dedup first: if a window is already viewing this device,
+    // focus it and discard the new deep link — reporting a view-only mismatch.
+    // Clone the label and drop the lock BEFORE calling set_focus(); on macOS
+    // set_focus pumps the AppKit run loop and can re-enter Tauri command
+    // handlers that also need this lock.
+    if let Some(device_id) = link.device() {
         let existing_label = {
-            let sessions = app.state::<SessionMap>();
-            let map = lock_or_recover(&sessions.0, "session_map");
-            map.get(&session_id).map(|e| e.window_label.clone())
+            let devices = app.state::<DeviceMap>();
+            let map = devices.0.lock();
+            map.get(device_id).cloned()
         }; // lock released here
         if let Some(label) = existing_label {
             if let Some(window) = app.get_webview_window(&label) {
                 if let Err(err) = window.set_focus() {
-                    eprintln!("Failed to focus existing session window {}: {}", label, err);
+                    tracing::warn!(label = %label, error = %err, "failed to focus existing device window");
                 }
+                tracing::info!(device_id, label = %label, "focused existing device window");
+                report_mode_conflict(app, &label, &link);
+                return true;
             }
-            return;
+        }
+    }
+
+    // Fallback: dedup by session id (covers older web builds and edge cases).
+    if let Some(session_id) = link.session() {
+        if let Some(label) = window_for_session(app, session_id) {
+            if let Some(window) = app.get_webview_window(&label) {
+                if let Err(err) = window.set_focus() {
+                    tracing::warn!(label = %label, error = %err, "failed to focus existing session window");
+                }
+            }
+            tracing::info!(session_id, label = %label, "focused existing session window");
+            report_mode_conflict(app, &label, &link);
+            return true;
         }
     }
 
     // No existing window matched — open a new session window.
-    create_session_window(app, url);
+    tracing::info!(session_id = ?link.session(), "opening new session window");
+    create_session_window(app, link)
 }
 
-/// Emit a deep-link-received event to a window with retry delays.
-/// Spawns a background thread that emits at 500ms and 1500ms to cover
-/// slow webview startup. Stops early if the target window is destroyed.
-fn emit_with_retry(app: &tauri::AppHandle, label: &str, url: String) {
-    let handle = app.clone();
-    let label = label.to_string();
-    std::thread::spawn(move || {
-        for delay_ms in [500, 1500] {
-            std::thread::sleep(std::time::Duration::from_millis(delay_ms));
-            // Stop if the target window no longer exists
-            if handle.get_webview_window(&label).is_none() {
-                eprintln!("Window {} gone — stopping deep link emission", label);
-                return;
+/// Route the link the viewer was launched with the way `route_deep_link`
+/// routes later ones — one `route` per session — so a multi-session link
+/// opens every session, not just the first. Returns whether any session
+/// window is on screen afterwards.
+fn route_launch_link(
+    url: &str,
+    accept: impl FnOnce(&str) -> Result<DeepLinkParams, String>,
+    mut route: impl FnMut(DeepLinkParams) -> bool,
+) -> bool {
+    match accept(url) {
+        Ok(link) => {
+            // Route every session, even after one has opened a window.
+            let mut opened = false;
+            for link in link.split_sessions() {
+                opened |= route(link);
+            }
+            opened
+        }
+        Err(err) => {
+            tracing::warn!(error = %err, "rejected invalid deep link at launch");
+            false
+        }
+    }
+}
+
+/// Payload of `session-mode-conflict`: a link asked for `requested_view_only`,
+/// but the window it focused was opened with `view_only`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionModeConflict {
+    session_id: Option<String>,
+    view_only: bool,
+    requested_view_only: bool,
+}
+
+/// The conflict to report when `link` lands on the existing window `label`,
+/// or `None` when the modes agree. A window with no recorded link has nothing
+/// to conflict with.
+fn session_mode_conflict(
+    windows: &HashMap<String, DeepLinkParams>,
+    label: &str,
+    link: &DeepLinkParams,
+) -> Option<SessionModeConflict> {
+    let view_only = windows.get(label)?.view_only();
+    (view_only != link.view_only()).then(|| SessionModeConflict {
+        session_id: link.session().map(str::to_string),
+        view_only,
+        requested_view_only: link.view_only(),
+    })
+}
+
+/// Tell the window `label` that `link` asked for the other mode. The window
+/// keeps its mode; switching a live session is the tech's call, not a link's.
+fn report_mode_conflict(app: &tauri::AppHandle, label: &str, link: &DeepLinkParams) {
+    let conflict = app
+        .try_state::<WindowLinks>()
+        .and_then(|windows| session_mode_conflict(&windows.0.lock(), label, link));
+    let Some(conflict) = conflict else {
+        return;
+    };
+    tracing::info!(
+        label,
+        view_only = conflict.view_only,
+        requested_view_only = conflict.requested_view_only,
+        "deep link mode conflicts with the open window"
+    );
+    if let Err(err) = app.emit_to(label, "session-mode-conflict", conflict) {
+        tracing::warn!(label, error = %err, "failed to emit session-mode-conflict");
+    }
+}
+
+// ── Session window geometry ──────────────────────────────────────────────
+// Techs park session windows on particular monitors, so each window's last
+// position and size are remembered per host (`host_label`, else session id)
+// and reused the next time that host opens. Geometry is tracked in memory on
+// every move/resize and written to `window-geometry.json` in the app config
+// dir when the window closes — a drag fires dozens of `Moved` events and none
+// of them need a disk write.
+
+const WINDOW_GEOMETRY_FILE: &str = "window-geometry.json";
+const DEFAULT_SESSION_WIDTH: f64 = 1280.0;
+const DEFAULT_SESSION_HEIGHT: f64 = 800.0;
+/// Smallest restored size; anything smaller is treated as corrupt.
+const MIN_RESTORED_WIDTH: f64 = 400.0;
+const MIN_RESTORED_HEIGHT: f64 = 300.0;
+/// How much of a restored window must overlap a connected monitor, so the
+/// title bar can't land somewhere the tech can't grab it.
+const MIN_VISIBLE_WIDTH: f64 = 100.0;
+const MIN_VISIBLE_HEIGHT: f64 = 50.0;
+
+/// Outer position and inner size of a window, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+struct WindowGeometry {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// A connected monitor's work area, in logical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MonitorRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Default)]
+struct WindowGeometryData {
+    /// Geometry key → last known geometry. This is what goes to disk.
+    saved: HashMap<String, WindowGeometry>,
+    /// Window label → geometry key, for windows opened with one.
+    keys: HashMap<String, String>,
+}
+
+struct WindowGeometryStore {
+    path: Option<PathBuf>,
+    data: Mutex<WindowGeometryData>,
+}
+
+/// Saved geometry from the file's contents. A missing or corrupt file restores
+/// nothing rather than failing the launch.
+fn parse_window_geometry(contents: &str) -> HashMap<String, WindowGeometry> {
+    serde_json::from_str(contents).unwrap_or_default()
+}
+
+/// Whether `geometry` is sane and overlaps some connected monitor by at least
+/// `MIN_VISIBLE_WIDTH` x `MIN_VISIBLE_HEIGHT`. A monitor that has since been
+/// unplugged fails this, and the window opens centered at the default size.
+fn geometry_is_visible(geometry: &WindowGeometry, monitors: &[MonitorRect]) -> bool {
+    let values = [geometry.x, geometry.y, geometry.width, geometry.height];
+    if values.iter().any(|v| !v.is_finite())
+        || geometry.width < MIN_RESTORED_WIDTH
+        || geometry.height < MIN_RESTORED_HEIGHT
+    {
+        return false;
+    }
+    monitors.iter().any(|m| {
+        let overlap_w = (geometry.x + geometry.width).min(m.x + m.width) - geometry.x.max(m.x);
+        let overlap_h = (geometry.y + geometry.height).min(m.y + m.height) - geometry.y.max(m.y);
+        overlap_w >= MIN_VISIBLE_WIDTH && overlap_h >= MIN_VISIBLE_HEIGHT
+    })
+}
+
+fn connected_monitors(app: &tauri::AppHandle) -> Vec<MonitorRect> {
+    app.available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|monitor| {
+            let scale = monitor.scale_factor();
+            let position = monitor.position().to_logical::<f64>(scale);
+            let size = monitor.size().to_logical::<f64>(scale);
+            MonitorRect {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
             }
-            if let Err(err) = handle.emit_to(&label, "deep-link-received", url.clone()) {
-                eprintln!("Failed to emit deep-link-received to {}: {}", label, err);
+        })
+        .collect()
+}
+
+fn load_window_geometry(app: &tauri::AppHandle) -> WindowGeometryStore {
+    let path = app
+        .path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(WINDOW_GEOMETRY_FILE));
+    let saved = path
+        .as_deref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|contents| parse_window_geometry(&contents))
+        .unwrap_or_default();
+    WindowGeometryStore {
+        path,
+        data: Mutex::new(WindowGeometryData {
+            saved,
+            keys: HashMap::new(),
+        }),
+    }
+}
+
+/// Geometry to open a window for `key` with, if it was saved and still fits
+/// on a connected monitor. Also ties `label` to `key` so moves are recorded.
+fn restore_window_geometry(
+    app: &tauri::AppHandle,
+    label: &str,
+    key: Option<&str>,
+) -> Option<WindowGeometry> {
+    let store = app.try_state::<WindowGeometryStore>()?;
+    let key = key?;
+    let saved = {
+        let mut data = store.data.lock();
+        data.keys.insert(label.to_string(), key.to_string());
+        data.saved.get(key).copied()
+    };
+    saved.filter(|geometry| geometry_is_visible(geometry, &connected_monitors(app)))
+}
+
+/// Record the current geometry of `label` after a move or resize. Minimized
+/// and maximized states are skipped so restoring never opens a window at its
+/// taskbar-icon or full-screen bounds.
+fn record_window_geometry(app: &tauri::AppHandle, label: &str) {
+    let Some(store) = app.try_state::<WindowGeometryStore>() else {
+        return;
+    };
+    let Some(window) = app.get_webview_window(label) else {
+        return;
+    };
+    if window.is_minimized().unwrap_or(true) || window.is_maximized().unwrap_or(true) {
+        return;
+    }
+    let (Ok(scale), Ok(position), Ok(size)) = (
+        window.scale_factor(),
+        window.outer_position(),
+        window.inner_size(),
+    ) else {
+        return;
+    };
+    let position = position.to_logical::<f64>(scale);
+    let size = size.to_logical::<f64>(scale);
+    let mut data = store.data.lock();
+    if let Some(key) = data.keys.get(label).cloned() {
+        data.saved.insert(
+            key,
+            WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            },
+        );
+    }
+}
+
+/// Forget `label` and flush saved geometry to disk. Called when a window is
+/// destroyed; failures are logged, never surfaced.
+fn save_window_geometry(app: &tauri::AppHandle, label: &str) {
+    let Some(store) = app.try_state::<WindowGeometryStore>() else {
+        return;
+    };
+    let contents = {
+        let mut data = store.data.lock();
+        if data.keys.remove(label).is_none() {
+            return;
+        }
+        serde_json::to_string_pretty(&data.saved)
+    };
+    let Some(path) = store.path.as_deref() else {
+        return;
+    };
+    let result = contents.map_err(|e| e.to_string()).and_then(|contents| {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    });
+    if let Err(err) = result {
+        tracing::warn!(path = %path.display(), error = %err, "failed to save window geometry");
+    }
+}
+
+// ── Viewer settings ──────────────────────────────────────────────────────
+// Preferences that outlive a session, in `settings.json` in the app config
+// dir. For now only how new session windows open: the built-in 1280x800 is
+// cramped on a 4K display, and techs there maximize every window by hand.
+// A window whose host has saved geometry still opens at that position and size.
+
+const VIEWER_SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+struct ViewerSettings {
+    /// Inner size of a session window with no saved geometry, in logical pixels.
+    default_width: f64,
+    default_height: f64,
+    /// Maximize every new session window once it is built.
+    start_maximized: bool,
+}
+
+impl Default for ViewerSettings {
+    fn default() -> Self {
+        Self {
+            default_width: DEFAULT_SESSION_WIDTH,
+            default_height: DEFAULT_SESSION_HEIGHT,
+            start_maximized: false,
+        }
+    }
+}
+
+impl ViewerSettings {
+    /// Rejects a default size no window could sensibly open at.
+    fn validate(&self) -> Result<(), String> {
+        let (width, height) = (self.default_width, self.default_height);
+        if !width.is_finite() || !height.is_finite() {
+            return Err("Default window size must be a finite number".to_string());
+        }
+        if width < MIN_RESTORED_WIDTH || height < MIN_RESTORED_HEIGHT {
+            return Err(format!(
+                "Default window size must be at least {}x{}",
+                MIN_RESTORED_WIDTH, MIN_RESTORED_HEIGHT
+            ));
+        }
+        Ok(())
+    }
+}
+
+struct ViewerSettingsStore {
+    path: Option<PathBuf>,
+    settings: Mutex<ViewerSettings>,
+}
+
+/// Settings from the file's contents. A missing, corrupt or out-of-range file
+/// falls back to the defaults rather than failing the launch.
+fn parse_viewer_settings(contents: &str) -> ViewerSettings {
+    serde_json::from_str::<ViewerSettings>(contents)
+        .ok()
+        .filter(|settings| settings.validate().is_ok())
+        .unwrap_or_default()
+}
+
+fn load_viewer_settings(app: &tauri::AppHandle) -> ViewerSettingsStore {
+    let path = app
+        .path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(VIEWER_SETTINGS_FILE));
+    let settings = path
+        .as_deref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|contents| parse_viewer_settings(&contents))
+        .unwrap_or_default();
+    ViewerSettingsStore {
+        path,
+        settings: Mutex::new(settings),
+    }
+}
+
+fn current_viewer_settings(app: &tauri::AppHandle) -> ViewerSettings {
+    app.try_state::<ViewerSettingsStore>()
+        .map(|store| *store.settings.lock())
+        .unwrap_or_default()
+}
+
+/// Called by a preferences screen to show the current settings.
+#[tauri::command]
+fn get_viewer_settings(state: tauri::State<'_, ViewerSettingsStore>) -> ViewerSettings {
+    *state.settings.lock()
+}
+
+/// Replace the settings and write them to disk. Takes effect for the next
+/// session window; open ones keep their size.
+#[tauri::command]
+fn set_viewer_settings(
+    state: tauri::State<'_, ViewerSettingsStore>,
+    settings: ViewerSettings,
+) -> Result<ViewerSettings, String> {
+    settings.validate()?;
+    if let Some(path) = state.path.as_deref() {
+        let contents = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create settings folder: {}", e))?;
+        }
+        std::fs::write(path, contents).map_err(|e| format!("Failed to save settings: {}", e))?;
+    }
+    *state.settings.lock() = settings;
+    tracing::info!(?settings, "viewer settings changed");
+    Ok(settings)
+}
+
+// ── Always on top ────────────────────────────────────────────────────────
+// Techs watching a critical machine pin its session window above everything
+// else. The choice is remembered per host, under the same key as window
+// geometry, in `always-on-top.json` in the app config dir, and the host's
+// next window opens pinned.
+
+const ALWAYS_ON_TOP_FILE: &str = "always-on-top.json";
+
+struct AlwaysOnTopStore {
+    path: Option<PathBuf>,
+    /// Geometry keys of the hosts whose windows open pinned.
+    hosts: Mutex<BTreeSet<String>>,
+}
+
+/// Pinned hosts from the file's contents. A missing or corrupt file pins
+/// nothing rather than failing the launch.
+fn parse_always_on_top(contents: &str) -> BTreeSet<String> {
+    serde_json::from_str(contents).unwrap_or_default()
+}
+
+fn load_always_on_top(app: &tauri::AppHandle) -> AlwaysOnTopStore {
+    let path = app
+        .path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(ALWAYS_ON_TOP_FILE));
+    let hosts = path
+        .as_deref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|contents| parse_always_on_top(&contents))
+        .unwrap_or_default();
+    AlwaysOnTopStore {
+        path,
+        hosts: Mutex::new(hosts),
+    }
+}
+
+/// Whether a window opened for geometry key `key` should start pinned.
+fn host_always_on_top(app: &tauri::AppHandle, key: Option<&str>) -> bool {
+    let Some(key) = key else {
+        return false;
+    };
+    app.try_state::<AlwaysOnTopStore>()
+        .is_some_and(|store| store.hosts.lock().contains(key))
+}
+
+/// Remember `enabled` for `key` and write the pinned hosts to disk. Failures
+/// are logged; the window itself is already pinned or unpinned.
+fn remember_always_on_top(app: &tauri::AppHandle, key: &str, enabled: bool) {
+    let Some(store) = app.try_state::<AlwaysOnTopStore>() else {
+        return;
+    };
+    let contents = {
+        let mut hosts = store.hosts.lock();
+        let changed = if enabled {
+            hosts.insert(key.to_string())
+        } else {
+            hosts.remove(key)
+        };
+        if !changed {
+            return;
+        }
+        serde_json::to_string_pretty(&*hosts)
+    };
+    let Some(path) = store.path.as_deref() else {
+        return;
+    };
+    let result = contents.map_err(|e| e.to_string()).and_then(|contents| {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    });
+    if let Err(err) = result {
+        tracing::warn!(path = %path.display(), error = %err, "failed to save always-on-top hosts");
+    }
+}
+
+/// Pin (or unpin) the window showing `session_id` above all other windows,
+/// and remember the choice for that host's next window.
+#[tauri::command]
+fn set_window_always_on_top(
+    app: tauri::AppHandle,
+    session_id: String,
+    enabled: bool,
+    state: tauri::State<'_, SessionMap>,
+) -> Result<(), String> {
+    // Clone the label and drop the lock before touching the window.
+    let label = state
+        .0
+        .lock()
+        .get(&session_id)
+        .map(|entry| entry.window_label.clone());
+    let window = label
+        .and_then(|label| app.get_webview_window(&label))
+        .ok_or_else(|| format!("No open window for session {}", session_id))?;
+    window
+        .set_always_on_top(enabled)
+        .map_err(|e| format!("Failed to pin session {}: {}", session_id, e))?;
+    tracing::info!(session_id = %session_id, label = %window.label(), enabled, "set always on top");
+    let key = app.try_state::<WindowLinks>().and_then(|windows| {
+        let windows = windows.0.lock();
+        windows
+            .get(window.label())
+            .and_then(|link| link.geometry_key().map(str::to_string))
+    });
+    if let Some(key) = key {
+        remember_always_on_top(&app, &key, enabled);
+    }
+    Ok(())
+}
+
+// ── Session restore ──────────────────────────────────────────────────────
+// A crash or an update mid-shift would otherwise drop every open session and
+// leave the tech re-clicking each device. Registered sessions, with the link
+// each window was opened with, are written to `sessions.json` in the app config
+// dir whenever the set changes, and rewritten every `SESSION_RESTORE_REFRESH`
+// while any are open so a long session isn't mistaken for a dead one. The next
+// launch offers them back through `restorable_sessions` / `restore_sessions`;
+// anything unseen for longer than `SESSION_RESTORE_MAX_AGE` is never offered.
+
+const SESSION_RESTORE_FILE: &str = "sessions.json";
+/// Past this the remote end has long since given up on the session.
+const SESSION_RESTORE_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+const SESSION_RESTORE_REFRESH: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// One entry of `sessions.json`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct RestorableSession {
+    session_id: String,
+    host_label: Option<String>,
+    /// The link the session's window was opened with, replayed to reopen it.
+    url: String,
+    /// Unix seconds when the session was last known to be open.
+    last_seen: u64,
+}
+
+/// A session offered back at launch. The link stays in Rust.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+struct RestoreOffer {
+    session_id: String,
+    host_label: Option<String>,
+    last_seen: u64,
+}
+
+struct SessionRestoreStore {
+    path: Option<PathBuf>,
+    /// Window label → the link it was opened with.
+    links: Mutex<HashMap<String, String>>,
+    /// Sessions read at launch, until `restore_sessions` takes them.
+    offered: Mutex<Vec<RestorableSession>>,
+}
+
+/// Sessions from the file's contents seen within `max_age` of `now`, keeping
+/// the first entry for each id. A missing or corrupt file restores nothing.
+fn parse_restorable_sessions(
+    contents: &str,
+    now: u64,
+    max_age: std::time::Duration,
+) -> Vec<RestorableSession> {
+    let saved: Vec<RestorableSession> = serde_json::from_str(contents).unwrap_or_default();
+    let mut fresh: Vec<RestorableSession> = Vec::new();
+    for session in saved {
+        let stale = now.saturating_sub(session.last_seen) > max_age.as_secs();
+        if !stale && !fresh.iter().any(|s| s.session_id == session.session_id) {
+            fresh.push(session);
+        }
+    }
+    fresh
+}
+
+/// What to write: each registered session whose window still has the link it
+/// was opened with, ordered by session id. The hostname learned from the
+/// handshake wins over the link's `host_label`.
+fn restorable_sessions_at(
+    sessions: &HashMap<String, SessionEntry>,
+    links: &HashMap<String, String>,
+    now: u64,
+) -> Vec<RestorableSession> {
+    let mut out: Vec<RestorableSession> = sessions
+        .iter()
+        .filter_map(|(session_id, entry)| {
+            let url = links.get(&entry.window_label)?;
+            let host_label = entry.hostname.clone().or_else(|| {
+                let link = DeepLinkParams::parse(url).ok()?;
+                link.param("host_label").map(str::to_string)
+            });
+            Some(RestorableSession {
+                session_id: session_id.clone(),
+                host_label,
+                url: url.clone(),
+                last_seen: now,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+    out
+}
+
+fn load_session_restore(app: &tauri::AppHandle) -> SessionRestoreStore {
+    let path = app
+        .path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(SESSION_RESTORE_FILE));
+    let offered = path
+        .as_deref()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|contents| parse_restorable_sessions(&contents, unix_now(), SESSION_RESTORE_MAX_AGE))
+        .unwrap_or_default();
+    SessionRestoreStore {
+        path,
+        links: Mutex::new(HashMap::new()),
+        offered: Mutex::new(offered),
+    }
+}
+
+/// Write the current sessions to disk. Skipped once the app is exiting, since
+/// windows torn down by the exit are not sessions the tech ended; failures are
+/// logged, never surfaced.
+fn save_session_restore(app: &tauri::AppHandle) {
+    if EXIT_REQUESTED.load(std::sync::atomic::Ordering::SeqCst) {
+        return;
+    }
+    let (Some(store), Some(sessions)) = (
+        app.try_state::<SessionRestoreStore>(),
+        app.try_state::<SessionMap>(),
+    ) else {
+        return;
+    };
+    let Some(path) = store.path.as_deref() else {
+        return;
+    };
+    // Snapshot each map in turn; never hold both locks at once.
+    let sessions = sessions.0.lock().clone();
+    let links = store.links.lock().clone();
+    let restorable = restorable_sessions_at(&sessions, &links, unix_now());
+    let result = serde_json::to_string_pretty(&restorable)
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
             }
+            std::fs::write(path, contents).map_err(|e| e.to_string())
+        });
+    if let Err(err) = result {
+        tracing::warn!(path = %path.display(), error = %err, "failed to save sessions");
+    }
+}
+
+/// Keep `last_seen` current while sessions are open.
+fn refresh_session_restore(app: tauri::AppHandle) {
+    loop {
+        std::thread::sleep(SESSION_RESTORE_REFRESH);
+        let open = !app.state::<SessionMap>().0.lock().is_empty();
+        if open {
+            save_session_restore(&app);
         }
+    }
+}
+
+/// Sessions left open by the previous run, for the idle card to offer back.
+#[tauri::command]
+fn restorable_sessions(state: tauri::State<'_, SessionRestoreStore>) -> Vec<RestoreOffer> {
+    state
+        .offered
+        .lock()
+        .iter()
+        .map(|session| RestoreOffer {
+            session_id: session.session_id.clone(),
+            host_label: session.host_label.clone(),
+            last_seen: session.last_seen,
+        })
+        .collect()
+}
+
+/// Reopen the offered sessions named in `session_ids` (all of them when
+/// omitted), routed like the links they were opened with. The offer is
+/// one-shot: whatever isn't reopened is dropped, so an empty list declines.
+/// Returns how many were reopened.
+#[tauri::command]
+fn restore_sessions(
+    app: tauri::AppHandle,
+    session_ids: Option<Vec<String>>,
+    state: tauri::State<'_, SessionRestoreStore>,
+) -> usize {
+    let offered = std::mem::take(&mut *state.offered.lock());
+    let links: Vec<DeepLinkParams> = offered
+        .into_iter()
+        .filter(|session| {
+            session_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&session.session_id))
+        })
+        // Accepted when first opened; a signature check now would only fail
+        // on the link's age.
+        .filter_map(|session| match DeepLinkParams::parse(&session.url) {
+            Ok(link) => Some(link),
+            Err(err) => {
+                tracing::warn!(session_id = %session.session_id, error = %err, "dropped saved session");
+                None
+            }
+        })
+        .collect();
+    let count = links.len();
+    // Off the command thread before building windows (see issue #1409).
+    std::thread::spawn(move || {
+        let h = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            for link in links {
+                route_session_link(&h, link);
+            }
+        });
     });
+    count
+}
+
+/// Payload of the `session-window-created` / `session-window-failed` pair,
+/// broadcast once `build()` settles. `error` is set only on failure.
+#[derive(Clone, serde::Serialize)]
+struct SessionWindowEvent {
+    label: String,
+    session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 /// Create a new WebviewWindow for an independent remote desktop session.
@@ -741,65 +2320,126 @@ fn emit_with_retry(app: &tauri::AppHandle, label: &str, url: String) {
 /// startup needs to know: if the deep link that launched us produces no window
 /// and nothing else is showing, the process would sit alive and invisible —
 /// the invisible-process symptom this change exists to remove.
-fn create_session_window(app: &tauri::AppHandle, url: String) -> bool {
-    let url = match validate_deep_link(&url) {
-        Ok(url) => url,
-        Err(err) => {
-            eprintln!("Rejected invalid deep link before window creation: {}", err);
-            return false;
+fn create_session_window(app: &tauri::AppHandle, link: DeepLinkParams) -> bool {
+    // Only live windows count, so closing one frees a slot.
+    let max_sessions = app
+        .try_state::<SessionLimit>()
+        .map(|limit| limit.0)
+        .unwrap_or(DEFAULT_MAX_SESSIONS);
+    if active_session_window_count(app) >= max_sessions {
+        tracing::info!(max_sessions, "rejected deep link: session window limit reached");
+        // Focus the newest window and tell it why nothing new opened, so the
+        // frontend can toast instead of the link silently doing nothing.
+        if let Some(window) = most_recent_session_window(app) {
+            let _ = window.set_focus();
+            if let Err(err) = app.emit_to(window.label(), "session-limit-reached", max_sessions) {
+                tracing::warn!(error = %err, "failed to emit session-limit-reached");
+            }
         }
-    };
-
-    if active_session_window_count(app) >= MAX_SESSION_WINDOWS {
-        eprintln!(
-            "Rejected deep link because session window limit ({}) is reached",
-            MAX_SESSION_WINDOWS
-        );
-        focus_any_session_window(app);
         // The limit is only reachable when windows already exist, so something
         // is on screen — just not a new one.
         return true;
     }
 
-    let n = {
+    let pending_labels: Vec<String> = app
+        .try_state::<DeepLinkState>()
+        .map(|state| state.0.lock().keys().cloned().collect())
+        .unwrap_or_default();
+    let label = {
         let counter = app.state::<WindowCounter>();
-        let mut c = lock_or_recover(&counter.0, "window_counter");
-        *c += 1;
-        *c
+        let mut c = counter.0.lock();
+        next_session_label(&mut c, |label| {
+            app.get_webview_window(label).is_some() || pending_labels.iter().any(|l| l == label)
+        })
     };
-    let label = format!("session-{}", n);
 
     // Store pending deep link for the new window
     if let Some(state) = app.try_state::<DeepLinkState>() {
-        let mut links = lock_or_recover(&state.0, "deep_link_state");
-        links.insert(label.clone(), url.clone());
+        let mut links = state.0.lock();
+        links.insert(label.clone(), link.url.clone());
+    }
+    if let Some(windows) = app.try_state::<WindowLinks>() {
+        windows.0.lock().insert(label.clone(), link.clone());
     }
 
-    match WebviewWindowBuilder::new(app, &label, WebviewUrl::App("index.html".into()))
-        .title("Connecting...")
-        .inner_size(1280.0, 800.0)
-        .build()
-    {
-        Ok(_) => {
+    // The relay rides in the page URL so the frontend has it before the deep
+    // link arrives and can include it in the code exchange.
+    let home_relay = app
+        .try_state::<HomeRelayRegion>()
+        .map(|home| home.0.clone())
+        .unwrap_or_else(|| DEFAULT_RELAY_REGION.to_string());
+    let page = format!("index.html?relay={}", link.relay_region(&home_relay));
+    let builder = WebviewWindowBuilder::new(app, &label, WebviewUrl::App(page.into()))
+        .title(link.window_title())
+        .always_on_top(host_always_on_top(app, link.geometry_key()));
+    let settings = current_viewer_settings(app);
+    let builder = match restore_window_geometry(app, &label, link.geometry_key()) {
+        Some(geometry) => builder
+            .position(geometry.x, geometry.y)
+            .inner_size(geometry.width, geometry.height),
+        None => builder
+            .inner_size(settings.default_width, settings.default_height)
+            .center(),
+    };
+
+    match builder.build() {
+        Ok(window) => {
+            if settings.start_maximized {
+                if let Err(err) = window.maximize() {
+                    tracing::warn!(label = %label, error = %err, "failed to maximize session window");
+                }
+            }
             // A real session took over — retire the anchor window. Hidden, not
             // closed: it is still the process anchor. Failure is cosmetic (the
             // idle card lingers beside the session) and cannot strand the exit
             // path, so log and carry on.
             if let Some(main) = app.get_webview_window("main") {
                 if let Err(err) = main.hide() {
-                    eprintln!("Failed to hide the idle window: {}", err);
+                    tracing::warn!(error = %err, "failed to hide the idle window");
                 }
             }
-            emit_with_retry(app, &label, url);
+            tracing::info!(
+                label = %label,
+                session_id = ?link.session(),
+                view_only = link.view_only(),
+                "session window created"
+            );
+            if let Some(store) = app.try_state::<SessionRestoreStore>() {
+                store.links.lock().insert(label.clone(), link.url.clone());
+            }
+            let created = SessionWindowEvent {
+                label: label.clone(),
+                session_id: link.session().map(str::to_string),
+                error: None,
+            };
+            if let Err(err) = app.emit("session-window-created", created) {
+                tracing::warn!(error = %err, "failed to emit session-window-created");
+            }
+            // The URL is delivered when the webview calls `frontend_ready`;
+            // the frontend applies `view_only` from it.
             true
         }
         Err(e) => {
-            eprintln!("Failed to create session window: {}", e);
+            tracing::warn!(label = %label, error = %e, "failed to create session window");
             // Clean up orphaned deep link state
             if let Some(state) = app.try_state::<DeepLinkState>() {
-                let mut links = lock_or_recover(&state.0, "deep_link_state");
+                let mut links = state.0.lock();
                 links.remove(&label);
             }
+            if let Some(windows) = app.try_state::<WindowLinks>() {
+                windows.0.lock().remove(&label);
+            }
+            // Never hand the link to a window that is already showing a
+            // session — that would disconnect the tech's live session. Report
+            // the failure and let the frontend decide whether to retry.
+            let failure = SessionWindowEvent {
+                label,
+                session_id: link.session().map(str::to_string),
+                error: Some(e.to_string()),
+            };
+            if let Err(err) = app.emit("session-window-failed", failure) {
+                tracing::warn!(error = %err, "failed to emit session-window-failed");
+            }
             false
         }
     }
@@ -814,7 +2454,7 @@ fn show_idle_window(app: &tauri::AppHandle) {
     match app.get_webview_window("main") {
         Some(main) => {
             if let Err(err) = main.show() {
-                eprintln!("Could not show the idle window ({err}) — exiting rather than running invisibly");
+                tracing::error!(error = %err, "could not show the idle window — exiting rather than running invisibly");
                 request_exit(app, 1);
                 return;
             }
@@ -822,7 +2462,7 @@ fn show_idle_window(app: &tauri::AppHandle) {
             let _ = main.set_focus();
         }
         None => {
-            eprintln!("No 'main' window to show — exiting rather than running invisibly");
+            tracing::error!("no 'main' window to show — exiting rather than running invisibly");
             request_exit(app, 1);
         }
     }
@@ -840,12 +2480,99 @@ fn request_exit(app: &tauri::AppHandle, code: i32) {
 /// Whether the app should quit now that `label` was destroyed.
 ///
 /// Session windows are the app's reason to exist; `main` is only on screen
-/// after a manual launch. Either kind closing with no sessions left means
-/// nothing is on screen. Pure so this branch is testable without a Tauri
-/// runtime — getting it wrong either strands an invisible process or kills a
-/// live session out from under the user.
-fn should_exit_on_window_destroyed(label: &str, remaining_session_labels: &[String]) -> bool {
-    (label == "main" || label.starts_with("session-")) && remaining_session_labels.is_empty()
+/// after a manual launch or `close_all_sessions`. Either kind closing with no
+/// sessions left means nothing is on screen — unless the idle card is up, in
+/// which case the last session closing leaves the app running behind it. Pure
+/// so this branch is testable without a Tauri runtime — getting it wrong
+/// either strands an invisible process or kills a live session out from under
+/// the user.
+fn should_exit_on_window_destroyed(
+    label: &str,
+    remaining_session_labels: &[String],
+    main_visible: bool,
+) -> bool {
+    let owned = label == "main" || (label.starts_with("session-") && !main_visible);
+    owned && remaining_session_labels.is_empty()
+}
+
+/// How long windows get to disconnect from their remote after
+/// `session-closing` before `close_all_sessions` closes them regardless.
+const CLOSE_ALL_GRACE: std::time::Duration = std::time::Duration::from_millis(750);
+
+/// Tear down every session at once (end of shift): ask each window to
+/// disconnect, then close them all and put the idle card back up so the app
+/// stays running instead of exiting with the last window.
+fn close_all_session_windows(app: &tauri::AppHandle) {
+    let n = *app.state::<WindowCounter>().0.lock();
+    let mut labels: Vec<String> = (1..=n)
+        .map(|i| format!("session-{}", i))
+        .filter(|label| app.get_webview_window(label).is_some())
+        .collect();
+    for entry in app.state::<SessionMap>().0.lock().values() {
+        if !labels.contains(&entry.window_label) {
+            labels.push(entry.window_label.clone());
+        }
+    }
+
+    for label in &labels {
+        if let Err(e) = app.emit_to(label.as_str(), "session-closing", ()) {
+            tracing::warn!(label = %label, error = %e, "failed to emit session-closing");
+        }
+    }
+
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(CLOSE_ALL_GRACE);
+        let h = handle.clone();
+        let _ = handle.run_on_main_thread(move || {
+            show_idle_window(&h);
+            for label in labels.iter().filter(|label| *label != "main") {
+                if let Some(window) = h.get_webview_window(label) {
+                    if let Err(e) = window.close() {
+                        tracing::warn!(label = %label, error = %e, "failed to close window");
+                    }
+                }
+            }
+            // `Destroyed` prunes these per window; clear them outright so
+            // nothing survives from a window that refused to close.
+            h.state::<SessionMap>().0.lock().clear();
+            h.state::<DeviceMap>().0.lock().clear();
+            h.state::<DeepLinkState>().0.lock().clear();
+            h.state::<WindowLinks>().0.lock().clear();
+            h.state::<SessionActivity>().0.lock().clear();
+        });
+    });
+}
+
+#[tauri::command]
+fn close_all_sessions(app: tauri::AppHandle) {
+    close_all_session_windows(&app);
+}
+
+/// Tray icon with session-wide actions. Failing to build it costs only the
+/// menu, so the caller logs and carries on.
+fn build_tray(app: &tauri::App) -> tauri::Result<()> {
+    let close_all = MenuItem::with_id(
+        app,
+        "close-all-sessions",
+        "Close all sessions",
+        true,
+        None::<&str>,
+    )?;
+    let menu = Menu::with_items(app, &[&close_all])?;
+    let mut tray = TrayIconBuilder::with_id("main")
+        .tooltip("Breeze Viewer")
+        .menu(&menu)
+        .on_menu_event(|app, event| {
+            if event.id.as_ref() == "close-all-sessions" {
+                close_all_session_windows(app);
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        tray = tray.icon(icon.clone());
+    }
+    tray.build(app)?;
+    Ok(())
 }
 
 /// Update lifecycle status broadcast to all windows on the `update-status`
@@ -877,7 +2604,167 @@ enum UpdateStatus {
 /// update proceeds regardless of whether the UI is listening.
 fn emit_update_status(app: &tauri::AppHandle, status: UpdateStatus) {
     if let Err(e) = app.emit("update-status", status) {
-        eprintln!("Failed to emit update-status: {}", e);
+        tracing::warn!(error = %e, "failed to emit update-status");
+    }
+}
+
+/// Events a window can ask the backend to fan out to every window, so all
+/// sessions react to a shared condition the same way instead of each
+/// discovering it on its own.
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+enum SessionBroadcast {
+    /// Connectivity changed; sessions show a shared "reconnecting" banner
+    /// while offline and retry as soon as it returns.
+    NetworkStatus { online: bool },
+}
+
+#[derive(Clone, serde::Serialize)]
+struct NetworkStatus {
+    online: bool,
+}
+
+/// Last connectivity state broadcast, so several windows reporting the same
+/// change produce one `network-status-changed` event.
+struct LastNetworkStatus(Mutex<Option<bool>>);
+
+/// Record `online` and report whether it differs from what was last sent.
+fn network_status_changed(last: &mut Option<bool>, online: bool) -> bool {
+    last.replace(online) != Some(online)
+}
+
+#[tauri::command]
+fn broadcast_session_event(
+    app: tauri::AppHandle,
+    event: SessionBroadcast,
+    state: tauri::State<'_, LastNetworkStatus>,
+) {
+    match event {
+        SessionBroadcast::NetworkStatus { online } => {
+            if !network_status_changed(&mut state.0.lock(), online) {
+                return;
+            }
+            if let Err(e) = app.emit("network-status-changed", NetworkStatus { online }) {
+                tracing::warn!(error = %e, "failed to emit network-status-changed");
+            }
+        }
+    }
+}
+
+// ── Idle sessions ───────────────────────────────────────────────────────
+// Opt-in via `BREEZE_VIEWER_IDLE_TIMEOUT_MINS`. A window is tracked from
+// `register_session` until it disconnects or is destroyed; the frontend pings
+// `session_activity` on user input, and a watcher thread warns a minute before
+// closing any window that has gone quiet for the whole timeout.
+
+const IDLE_WARNING_LEAD: std::time::Duration = std::time::Duration::from_secs(60);
+const IDLE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+/// Shorter than this and the warning would fire almost as soon as a session
+/// connects.
+const MIN_IDLE_TIMEOUT_MINS: u64 = 2;
+const MAX_IDLE_TIMEOUT_MINS: u64 = 24 * 60;
+
+/// `BREEZE_VIEWER_IDLE_TIMEOUT_MINS` as a timeout: unset, zero, or garbage
+/// disables it; anything else is clamped to a sane range.
+fn idle_timeout_from_env(value: Option<&str>) -> Option<std::time::Duration> {
+    let mins = value
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&n| n > 0)?;
+    let mins = mins.clamp(MIN_IDLE_TIMEOUT_MINS, MAX_IDLE_TIMEOUT_MINS);
+    Some(std::time::Duration::from_secs(mins * 60))
+}
+
+struct IdleEntry {
+    last_activity: std::time::Instant,
+    warned: bool,
+}
+
+impl IdleEntry {
+    fn now() -> Self {
+        IdleEntry {
+            last_activity: std::time::Instant::now(),
+            warned: false,
+        }
+    }
+}
+
+/// Last user activity per session window label.
+struct SessionActivity(Mutex<HashMap<String, IdleEntry>>);
+
+#[derive(Debug, PartialEq, Eq)]
+enum IdleAction {
+    /// Warn once, with the time left before the window closes.
+    Warn(std::time::Duration),
+    Close,
+}
+
+/// What the watcher should do to each tracked window at `now`, ordered by label.
+fn idle_actions(
+    activity: &mut HashMap<String, IdleEntry>,
+    now: std::time::Instant,
+    timeout: std::time::Duration,
+) -> Vec<(String, IdleAction)> {
+    let mut actions: Vec<(String, IdleAction)> = activity
+        .iter_mut()
+        .filter_map(|(label, entry)| {
+            let idle = now.saturating_duration_since(entry.last_activity);
+            if idle >= timeout {
+                Some((label.clone(), IdleAction::Close))
+            } else if !entry.warned && idle + IDLE_WARNING_LEAD >= timeout {
+                entry.warned = true;
+                Some((label.clone(), IdleAction::Warn(timeout - idle)))
+            } else {
+                None
+            }
+        })
+        .collect();
+    actions.sort_by(|a, b| a.0.cmp(&b.0));
+    actions
+}
+
+/// Called by the frontend (throttled) on user input in a connected session.
+#[tauri::command]
+fn session_activity(window: tauri::WebviewWindow, state: tauri::State<'_, SessionActivity>) {
+    if let Some(entry) = state.0.lock().get_mut(window.label()) {
+        *entry = IdleEntry::now();
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IdleWarning {
+    seconds_remaining: u64,
+}
+
+/// Poll `SessionActivity` forever, warning and then closing idle windows.
+fn watch_idle_sessions(app: tauri::AppHandle, timeout: std::time::Duration) {
+    loop {
+        std::thread::sleep(IDLE_CHECK_INTERVAL);
+        let actions = {
+            let state = app.state::<SessionActivity>();
+            let mut activity = state.0.lock();
+            idle_actions(&mut activity, std::time::Instant::now(), timeout)
+        };
+        for (label, action) in actions {
+            match action {
+                IdleAction::Warn(remaining) => {
+                    let warning = IdleWarning {
+                        seconds_remaining: remaining.as_secs(),
+                    };
+                    if let Err(e) = app.emit_to(&label, "session-idle-warning", warning) {
+                        tracing::warn!(label = %label, error = %e, "failed to emit session-idle-warning");
+                    }
+                }
+                IdleAction::Close => {
+                    tracing::info!(label = %label, "closing idle session window");
+                    if let Some(window) = app.get_webview_window(&label) {
+                        if let Err(e) = window.close() {
+                            tracing::warn!(label = %label, error = %e, "failed to close idle window");
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -912,7 +2799,7 @@ async fn auto_update(app: tauri::AppHandle) {
     let updater = match app.updater() {
         Ok(u) => u,
         Err(e) => {
-            eprintln!("Failed to create updater: {}", e);
+            tracing::warn!(error = %e, "failed to create updater");
             return;
         }
     };
@@ -921,12 +2808,12 @@ async fn auto_update(app: tauri::AppHandle) {
         Ok(Some(update)) => update,
         Ok(None) => return, // already up to date
         Err(e) => {
-            eprintln!("Update check failed: {}", e);
+            tracing::warn!(error = %e, "update check failed");
             return;
         }
     };
 
-    eprintln!("Update {} available, downloading...", update.version);
+    tracing::info!(version = %update.version, "update available, downloading");
 
     let version = update.version.clone();
     emit_update_status(&app, UpdateStatus::Available { version: version.clone() });
@@ -935,14 +2822,14 @@ async fn auto_update(app: tauri::AppHandle) {
     let progress_version = version.clone();
     let mut downloaded: u64 = 0;
     // Throttle UI events to whole-percent changes so a fast download doesn't
-    // emit thousands of events; stderr logging stays per-chunk for forensics.
+    // emit thousands of events; the debug log stays per-chunk for forensics.
     let mut last_emitted_pct: i64 = -1;
     let bytes = match update
         .download(
             move |chunk_len, content_len| {
                 downloaded += chunk_len as u64;
                 if let Some(total) = content_len {
-                    eprintln!("Update download: {downloaded}/{total} bytes");
+                    tracing::debug!(downloaded, total, "update download progress");
                 }
                 let pct = download_percent(downloaded, content_len);
                 if pct != last_emitted_pct {
@@ -958,14 +2845,14 @@ async fn auto_update(app: tauri::AppHandle) {
                 }
             },
             || {
-                eprintln!("Update download finished");
+                tracing::info!("update download finished");
             },
         )
         .await
     {
         Ok(bytes) => bytes,
         Err(e) => {
-            eprintln!("Update download failed: {}", e);
+            tracing::warn!(error = %e, "update download failed");
             // Surface the failure so a banner already showing "Downloading…"
             // doesn't stay pinned forever, reintroducing the silent-crash look.
             emit_update_status(&app, UpdateStatus::Failed { version: version.clone() });
@@ -973,7 +2860,7 @@ async fn auto_update(app: tauri::AppHandle) {
         }
     };
 
-    eprintln!("Update {} downloaded", update.version);
+    tracing::info!(version = %update.version, "update downloaded");
 
     // Decide what to do with the download based on whether a remote session is
     // live. Restarting/installing mid-session would kill it, so an active
@@ -981,7 +2868,7 @@ async fn auto_update(app: tauri::AppHandle) {
     let has_active_sessions = app
         .try_state::<SessionMap>()
         .map(|s| {
-            let map = lock_or_recover(&s.0, "session_map");
+            let map = s.0.lock();
             !map.is_empty()
         })
         .unwrap_or(false);
@@ -993,14 +2880,14 @@ async fn auto_update(app: tauri::AppHandle) {
         #[cfg(not(target_os = "windows"))]
         {
             if let Err(e) = update.install(bytes) {
-                eprintln!("Deferred update disk-swap failed: {}", e);
+                tracing::error!(error = %e, "deferred update disk-swap failed");
             }
         }
         #[cfg(target_os = "windows")]
         {
             drop((update, bytes));
         }
-        eprintln!("Active remote session — deferring update to next launch");
+        tracing::info!("active remote session — deferring update to next launch");
         emit_update_status(&app, UpdateStatus::Deferred { version });
         return;
     }
@@ -1008,11 +2895,11 @@ async fn auto_update(app: tauri::AppHandle) {
     // No active session — stash the download and let the user choose via the
     // Ready prompt (apply_pending_update / dismiss_pending_update).
     if let Some(pending) = app.try_state::<PendingUpdate>() {
-        *lock_or_recover(&pending.0, "pending_update") = Some((update, bytes));
-        eprintln!("Update {} ready — awaiting user choice", version);
+        *pending.0.lock() = Some((update, bytes));
+        tracing::info!(version = %version, "update ready — awaiting user choice");
         emit_update_status(&app, UpdateStatus::Ready { version });
     } else {
-        eprintln!("PendingUpdate state missing; cannot present update prompt");
+        tracing::error!("PendingUpdate state missing; cannot present update prompt");
         // Defensive: clear the banner so a (theoretically impossible) missing
         // state doesn't leave it pinned on "Downloading…" — the silent-look
         // this feature exists to remove.
@@ -1023,6 +2910,7 @@ async fn auto_update(app: tauri::AppHandle) {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let mut builder = tauri::Builder::default()
+        .manage(EarlyDeepLinks(Mutex::new(EarlyDeepLinkQueue::default())))
         .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_clipboard_manager::init())
@@ -1030,14 +2918,29 @@ pub fn run() {
         .plugin(tauri_plugin_process::init())
         .invoke_handler(tauri::generate_handler![
             get_pending_deep_link,
+            frontend_ready,
             clear_pending_deep_link,
             register_session,
             unregister_session,
             register_device,
             update_session_hostname,
+            set_session_title,
+            list_sessions,
+            focus_session,
+            replace_session,
+            set_window_always_on_top,
+            open_session,
+            restorable_sessions,
+            restore_sessions,
+            broadcast_session_event,
+            session_activity,
+            close_all_sessions,
             apply_pending_update,
             dismiss_pending_update,
             get_scheme_registration_error,
+            get_recent_logs,
+            get_viewer_settings,
+            set_viewer_settings,
         ]);
 
     // Single instance plugin (desktop only) — ensures deep links open in existing
@@ -1071,8 +2974,8 @@ pub fn run() {
                 let h = handle.clone();
                 let _ = handle.run_on_main_thread(move || match url {
                     Some(url) => route_deep_link(&h, url),
-                    // No deep link — just activate. Focus most recent session window if any.
-                    None => focus_any_session_window(&h),
+                    // No deep link — just activate, bringing every window forward.
+                    None => activate_all_windows(&h),
                 });
             });
         }));
@@ -1080,11 +2983,20 @@ pub fn run() {
 
     let app = builder
         .setup(|app| {
+            match app.path().app_log_dir() {
+                Ok(dir) => {
+                    if let Err(e) = logging::init(&dir) {
+                        eprintln!("{}", e);
+                    }
+                }
+                Err(e) => eprintln!("No log directory for the viewer: {}", e),
+            }
+
             // Kept so the idle card can say registration failed instead of
             // claiming the viewer is ready when `breeze://` will not resolve.
             let scheme_registration = register_url_scheme();
             if let Err(ref err) = scheme_registration {
-                eprintln!("breeze:// registration failed: {err}");
+                tracing::warn!(error = %err, "breeze:// registration failed");
             }
             app.manage(SchemeRegistration(Mutex::new(scheme_registration.err())));
 
@@ -1096,13 +3008,42 @@ pub fn run() {
                 .and_then(|urls| urls.first().map(|u| u.to_string()));
 
             let initial_url =
-                initial_url.or_else(|| std::env::args().find(|arg| arg.starts_with("breeze:")));
+                initial_url.or_else(|| std::env::args().find(|arg| is_breeze_link(arg)));
 
             app.manage(DeepLinkState(Mutex::new(HashMap::new())));
             app.manage(SessionMap(Mutex::new(HashMap::new())));
             app.manage(DeviceMap(Mutex::new(HashMap::new())));
+            app.manage(WindowLinks(Mutex::new(HashMap::new())));
             app.manage(WindowCounter(Mutex::new(0)));
+            app.manage(load_window_geometry(app.handle()));
+            app.manage(load_viewer_settings(app.handle()));
+            app.manage(load_always_on_top(app.handle()));
+            app.manage(load_session_restore(app.handle()));
+            app.manage(SessionLimit(max_sessions_from_env(
+                std::env::var("BREEZE_VIEWER_MAX_SESSIONS").ok().as_deref(),
+            )));
+            app.manage(HomeRelayRegion(relay_region_from_env(
+                std::env::var("BREEZE_VIEWER_RELAY_REGION").ok().as_deref(),
+            )));
+            app.manage(LinkSigningKey(link_signing_key_from_env(
+                std::env::var("BREEZE_VIEWER_LINK_SECRET").ok().as_deref(),
+            )));
             app.manage(PendingUpdate(Mutex::new(None)));
+            app.manage(LastNetworkStatus(Mutex::new(None)));
+            app.manage(SessionActivity(Mutex::new(HashMap::new())));
+            if let Err(e) = build_tray(app) {
+                tracing::warn!(error = %e, "failed to create tray icon");
+            }
+            {
+                let handle = app.handle().clone();
+                std::thread::spawn(move || refresh_session_restore(handle));
+            }
+            if let Some(timeout) = idle_timeout_from_env(
+                std::env::var("BREEZE_VIEWER_IDLE_TIMEOUT_MINS").ok().as_deref(),
+            ) {
+                let handle = app.handle().clone();
+                std::thread::spawn(move || watch_idle_sessions(handle, timeout));
+            }
 
             // If launched with a deep link, defer session window creation to
             // the first event loop tick (setup runs before the loop starts).
@@ -1113,7 +3054,12 @@ pub fn run() {
                     // process alive with no window at all — the same invisible
                     // state the `else` branch below exists to prevent, reached
                     // by a different road.
-                    if !create_session_window(&handle, url) {
+                    let opened = route_launch_link(
+                        &url,
+                        |url| accept_deep_link(&handle, url),
+                        |link| route_session_link(&handle, link),
+                    );
+                    if !opened {
                         show_idle_window(&handle);
                     }
                 });
@@ -1169,6 +3115,23 @@ pub fn run() {
                 }
             });
 
+            // State is managed and on_open_url is wired: replay anything that
+            // arrived mid-setup (e.g. a second launch forwarded by the
+            // single-instance plugin), off the main thread for the same
+            // reason as on_open_url above.
+            let early = app.state::<EarlyDeepLinks>().0.lock().drain();
+            if !early.is_empty() {
+                let h = app.handle().clone();
+                std::thread::spawn(move || {
+                    let h2 = h.clone();
+                    let _ = h.run_on_main_thread(move || {
+                        for url in early {
+                            route_deep_link(&h2, url);
+                        }
+                    });
+                });
+            }
+
             // Fire-and-forget: update failures must never block the app.
             // Errors are logged inside auto_update(); panics are absorbed by the runtime.
             // Skipped in debug builds so `pnpm tauri dev` can't get clobbered by
@@ -1187,39 +3150,58 @@ pub fn run() {
     app.run(|app_handle, event| {
         match event {
             tauri::RunEvent::WindowEvent { label, event, .. } => {
+                if let WindowEvent::Moved(_) | WindowEvent::Resized(_) = event {
+                    record_window_geometry(app_handle, &label);
+                }
                 if let WindowEvent::Destroyed = event {
+                    tracing::info!(label = %label, "window destroyed");
+                    save_window_geometry(app_handle, &label);
                     if let Some(sessions) = app_handle.try_state::<SessionMap>() {
-                        let mut map = lock_or_recover(&sessions.0, "session_map");
+                        let mut map = sessions.0.lock();
                         map.retain(|_, entry| entry.window_label != label);
                     }
                     if let Some(devices) = app_handle.try_state::<DeviceMap>() {
-                        let mut map = lock_or_recover(&devices.0, "device_map");
+                        let mut map = devices.0.lock();
                         map.retain(|_, l| l != &label);
                     }
                     if let Some(links) = app_handle.try_state::<DeepLinkState>() {
-                        let mut map = lock_or_recover(&links.0, "deep_link_state");
+                        let mut map = links.0.lock();
                         map.remove(&label);
                     }
+                    if let Some(activity) = app_handle.try_state::<SessionActivity>() {
+                        activity.0.lock().remove(&label);
+                    }
+                    if let Some(windows) = app_handle.try_state::<WindowLinks>() {
+                        windows.0.lock().remove(&label);
+                    }
+                    if let Some(store) = app_handle.try_state::<SessionRestoreStore>() {
+                        store.links.lock().remove(&label);
+                    }
+                    save_session_restore(app_handle);
 
                     // When the last on-screen window closes, exit cleanly rather
                     // than leave an invisible process behind. See
                     // `should_exit_on_window_destroyed` for the rule.
                     let remaining_sessions: Vec<String> = {
                         let counter = app_handle.state::<WindowCounter>();
-                        let n = *lock_or_recover(&counter.0, "window_counter");
+                        let n = *counter.0.lock();
                         (1..=n)
                             .map(|i| format!("session-{}", i))
                             .filter(|l| l != &label && app_handle.get_webview_window(l).is_some())
                             .collect()
                     };
-                    if should_exit_on_window_destroyed(&label, &remaining_sessions) {
+                    let main_visible = app_handle
+                        .get_webview_window("main")
+                        .and_then(|main| main.is_visible().ok())
+                        .unwrap_or(false);
+                    if should_exit_on_window_destroyed(&label, &remaining_sessions, main_visible) {
                         request_exit(app_handle, 0);
                     }
                 }
             }
             #[cfg(target_os = "macos")]
             tauri::RunEvent::Reopen { .. } => {
-                focus_any_session_window(app_handle);
+                activate_all_windows(app_handle);
             }
             // Force a clean exit code on macOS. Without this, the
             // NSApplication terminate sequence can conflict with Rust
@@ -1237,6 +3219,7 @@ pub fn run() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::{Duration, Instant};
 
     /// `$APPIMAGE` must win over `current_exe()`. Inside an AppImage the latter
     /// is the ephemeral squashfs mount, so registering it produces a handler
@@ -1395,23 +3378,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn windows_open_command_quotes_exe_and_argument() {
+        assert_eq!(
+            windows_open_command(Path::new("/opt/Breeze Viewer/breeze-viewer.exe")).as_deref(),
+            Some(r#""/opt/Breeze Viewer/breeze-viewer.exe" "%1""#)
+        );
+        assert_eq!(windows_open_command(Path::new("rel/breeze-viewer.exe")), None);
+        assert_eq!(windows_open_command(Path::new("/opt/a\nb.exe")), None);
+        assert_eq!(windows_open_command(Path::new("/opt/a\"b.exe")), None);
+    }
+
+    #[test]
+    fn windows_registration_writes_the_open_command_last() {
+        let commands = windows_registration_commands(r#""C:\b.exe" "%1""#);
+        assert_eq!(commands.len(), 3);
+        assert!(commands
+            .iter()
+            .all(|args| args[0] == "add" && args.last().unwrap() == "/f"));
+        assert!(commands[1].contains(&"URL Protocol".to_string()));
+        assert_eq!(
+            commands[2],
+            vec![
+                "add",
+                r"HKCU\Software\Classes\breeze\shell\open\command",
+                "/ve",
+                "/d",
+                r#""C:\b.exe" "%1""#,
+                "/f",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_reg_default_value_reads_reg_sz() {
+        let stdout = "\r\nHKEY_CURRENT_USER\\Software\\Classes\\breeze\\shell\\open\\command\r\n    (Default)    REG_SZ    \"C:\\b.exe\" \"%1\"\r\n\r\n";
+        assert_eq!(
+            parse_reg_default_value(stdout).as_deref(),
+            Some(r#""C:\b.exe" "%1""#)
+        );
+        assert_eq!(parse_reg_default_value("ERROR: not found"), None);
+    }
+
     /// The riskiest new branch: closing the idle card must not kill a live
     /// session, and closing the last session must not strand a ghost process.
     #[test]
     fn exit_only_when_nothing_is_left_on_screen() {
         let one = vec!["session-1".to_string()];
         assert!(
-            !should_exit_on_window_destroyed("main", &one),
+            !should_exit_on_window_destroyed("main", &one, true),
             "closing the idle card with a live session must not exit"
         );
         assert!(
-            !should_exit_on_window_destroyed("session-2", &one),
+            !should_exit_on_window_destroyed("session-2", &one, false),
             "closing one of two sessions must not exit"
         );
-        assert!(should_exit_on_window_destroyed("session-1", &[]));
-        assert!(should_exit_on_window_destroyed("main", &[]));
+        assert!(should_exit_on_window_destroyed("session-1", &[], false));
+        assert!(should_exit_on_window_destroyed("main", &[], true));
+        assert!(
+            !should_exit_on_window_destroyed("session-1", &[], true),
+            "closing the last session behind the idle card must not exit"
+        );
         // Windows we don't own shouldn't drive the lifecycle either way.
-        assert!(!should_exit_on_window_destroyed("devtools", &[]));
+        assert!(!should_exit_on_window_destroyed("devtools", &[], false));
     }
 
     /// The entry is worthless unless it claims the scheme and forwards the URL,
@@ -1546,28 +3575,731 @@ mod tests {
         assert_eq!(first_deep_link_arg(&["breeze-helper".to_string()]), None);
         assert_eq!(first_deep_link_arg(&["breezed".to_string()]), None);
         // Scheme match is case-sensitive, intentionally kept in sync with the
-        // downstream parser (parse_breeze_deep_link strips "breeze:" case-sensitively).
+        // downstream parser (both go through is_breeze_link).
         // The registered scheme is lowercase, so an upcased variant must not match.
         assert_eq!(first_deep_link_arg(&["BREEZE://a".to_string()]), None);
     }
 
+    /// A connect link that passes validation, with `query` appended.
+    fn connect_link(query: &str) -> String {
+        format!("breeze://connect?code=c&api=https%3A%2F%2Fapi.example.com&{query}")
+    }
+
     #[test]
-    fn extract_device_id_cases() {
+    fn deep_link_params_session_cases() {
         let cases = [
-            ("breeze://connect?session=s&device=d1", Some("d1")),
-            ("breeze://connect?device=d1&session=s", Some("d1")),
-            ("breeze://connect?session=s&device=", None),
-            ("breeze://connect?session=s", None),
-            ("breeze://connect", None),
-            ("breeze://connect?session=s&xdevice=d1", None),
+            ("session=s1", Some("s1")),
+            ("session=a%2Fb", Some("a/b")),
+            ("session=k%3D%3D", Some("k==")),
+            ("session=a+b", Some("a b")),
+            ("session=s1#frag", Some("s1")),
+            ("session=s1&session=s2", Some("s1")),
+            ("#session=s1", None),
+            ("session=", None),
+            ("xsession=s1", None),
         ];
-        for (url, expected) in cases {
+        for (query, expected) in cases {
+            let url = connect_link(query);
             assert_eq!(
-                extract_device_id(url).as_deref(),
-                expected,
-                "extract_device_id({url:?})"
+                DeepLinkParams::parse(&url)
+                    .ok()
+                    .and_then(|link| link.session().map(str::to_string)),
+                expected.map(str::to_string),
+                "session of {url:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn deep_link_params_split_multi_session_links() {
+        let link = DeepLinkParams::parse(&connect_link(
+            "session=a&session=b&session=a&session=&session=c%2Fd&device=d1&host_label=H&relay=eu",
+        ))
+        .expect("valid link");
+        assert_eq!(link.sessions, ["a", "b", "c/d"]);
+
+        let split = link.split_sessions();
+        let ids: Vec<_> = split.iter().map(|l| l.session()).collect();
+        assert_eq!(ids, [Some("a"), Some("b"), Some("c/d")]);
+        for part in &split {
+            assert_eq!(part.sessions.len(), 1);
+            assert!(
+                part.url.starts_with("breeze://connect?session="),
+                "{}",
+                part.url
+            );
+            assert_eq!(part.param("code"), Some("c"));
+            assert_eq!(part.param("api"), Some("https://api.example.com"));
+            assert_eq!(part.param("relay"), Some("eu"));
+            assert_eq!(part.device(), None);
+            assert_eq!(part.param("host_label"), None);
+        }
+
+        // A single-session link is routed as-is, device and label included.
+        let single =
+            DeepLinkParams::parse(&connect_link("session=a&device=d1")).expect("valid link");
+        assert_eq!(single.split_sessions(), vec![single.clone()]);
+    }
+
+    #[test]
+    fn launch_link_routes_every_session() {
+        let url = connect_link("session=a&session=b&session=c");
+        let mut routed = Vec::new();
+        let opened = route_launch_link(&url, DeepLinkParams::parse, |link| {
+            routed.push(link.session().map(str::to_string));
+            // Only the last session's window builds.
+            link.session() == Some("c")
+        });
+        assert!(opened);
+        assert_eq!(
+            routed,
+            [Some("a"), Some("b"), Some("c")].map(|id| id.map(str::to_string))
+        );
+
+        let mut routed = 0;
+        let rejected = route_launch_link("breeze://settings", DeepLinkParams::parse, |_| {
+            routed += 1;
+            true
+        });
+        assert!(!rejected);
+        assert_eq!(routed, 0);
+    }
+
+    #[test]
+    fn deep_link_params_for_session_matches_the_parsed_link() {
+        let built = DeepLinkParams::for_session(
+            "a/b",
+            "c",
+            "https://api.example.com",
+            Some("DESKTOP-3OHCUL6"),
+            true,
+        )
+        .expect("valid session");
+        assert_eq!(
+            built.url,
+            "breeze://connect?session=a%2Fb&code=c&api=https%3A%2F%2Fapi.example.com\
+             &host_label=DESKTOP-3OHCUL6&view_only=1"
+        );
+        // Same as if the URL had arrived through the OS handler.
+        assert_eq!(DeepLinkParams::parse(&built.url), Ok(built.clone()));
+        assert!(built.view_only());
+        assert_eq!(built.window_title(), "Breeze — DESKTOP-3OHCUL6");
+
+        let plain =
+            DeepLinkParams::for_session("s", "c", "https://api.example.com", None, false).unwrap();
+        assert_eq!(plain.param("host_label"), None);
+        assert!(!plain.view_only());
+
+        assert!(
+            DeepLinkParams::for_session("", "c", "https://api.example.com", None, false).is_err()
+        );
+        assert!(DeepLinkParams::for_session("s", "c", "http://evil.example", None, false).is_err());
+        let long = "x".repeat(MAX_ID_PARAM_BYTES + 1);
+        assert!(
+            DeepLinkParams::for_session(&long, "c", "https://api.example.com", None, false)
+                .is_err()
+        );
+    }
+
+    fn sign_link(url: &str, key: &[u8]) -> String {
+        let parsed = parse_breeze_deep_link(url).unwrap();
+        let mut mac = HmacSha256::new_from_slice(key).unwrap();
+        mac.update(canonical_link_message(&parsed).as_bytes());
+        format!("{url}&sig={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    #[test]
+    fn canonical_link_message_sorts_params_and_skips_sig() {
+        let parsed =
+            parse_breeze_deep_link("breeze://connect/?session=s&exp=9&code=a+b&sig=ff").unwrap();
+        assert_eq!(
+            canonical_link_message(&parsed),
+            "connect\ncode=a+b&exp=9&session=s"
+        );
+    }
+
+    #[test]
+    fn signed_links_verify_only_when_untampered_and_unexpired() {
+        let key = b"shared-secret";
+        let url = connect_link("session=s&exp=2000");
+        let signed = sign_link(&url, key);
+        assert_eq!(verify_link_signature(&signed, key, 1000), Ok(()));
+
+        // Parameter order doesn't matter.
+        let reordered = signed.replacen("session=s&exp=2000", "exp=2000&session=s", 1);
+        assert_eq!(verify_link_signature(&reordered, key, 1000), Ok(()));
+
+        let tampered = signed.replacen("session=s", "session=attacker", 1);
+        assert!(verify_link_signature(&tampered, key, 1000)
+            .unwrap_err()
+            .contains("invalid"));
+        assert!(verify_link_signature(&signed, b"other-secret", 1000).is_err());
+        assert!(verify_link_signature(&signed, key, 2000)
+            .unwrap_err()
+            .contains("expired"));
+        assert!(verify_link_signature(&url, key, 1000)
+            .unwrap_err()
+            .contains("not signed"));
+
+        let no_exp = sign_link(&connect_link("session=s"), key);
+        assert!(verify_link_signature(&no_exp, key, 1000)
+            .unwrap_err()
+            .contains("expiry"));
+    }
+
+    #[test]
+    fn link_signing_is_off_without_a_secret() {
+        assert_eq!(link_signing_key_from_env(None), None);
+        assert_eq!(link_signing_key_from_env(Some("  ")), None);
+        assert_eq!(
+            link_signing_key_from_env(Some(" s3cret ")).as_deref(),
+            Some(b"s3cret".as_slice())
+        );
+    }
+
+    #[test]
+    fn deep_link_params_device_cases() {
+        let cases = [
+            ("session=s&device=d1", Some("d1")),
+            ("device=d1&session=s", Some("d1")),
+            ("session=s&device=", None),
+            ("session=s", None),
+            ("session=s&xdevice=d1", None),
+        ];
+        for (query, expected) in cases {
+            let link = DeepLinkParams::parse(&connect_link(query)).expect("valid link");
+            assert_eq!(link.device(), expected, "device of {query:?}");
+        }
+    }
+
+    #[test]
+    fn deep_link_params_keep_extra_session_options() {
+        let link = DeepLinkParams::parse(&connect_link(
+            "session=s&view_only=1&relay=eu&host_label=DESKTOP-3OHCUL6",
+        ))
+        .expect("valid link");
+        assert_eq!(link.session(), Some("s"));
+        assert_eq!(link.extra.get("relay").map(String::as_str), Some("eu"));
+        assert!(link.view_only());
+        assert_eq!(link.window_title(), "Breeze — DESKTOP-3OHCUL6");
+
+        let plain = DeepLinkParams::parse(&connect_link("session=s")).expect("valid link");
+        assert!(!plain.view_only());
+        assert_eq!(plain.window_title(), "Breeze — s");
+    }
+
+    #[test]
+    fn session_mode_conflict_reported_only_when_modes_differ() {
+        let view_only =
+            DeepLinkParams::parse(&connect_link("session=s&view_only=1")).expect("valid link");
+        let control = DeepLinkParams::parse(&connect_link("session=s")).expect("valid link");
+        let modes = HashMap::from([("session-1".to_string(), control.clone())]);
+
+        assert_eq!(
+            session_mode_conflict(&modes, "session-1", &view_only),
+            Some(SessionModeConflict {
+                session_id: Some("s".to_string()),
+                view_only: false,
+                requested_view_only: true,
+            })
+        );
+        assert_eq!(session_mode_conflict(&modes, "session-1", &control), None);
+        // No link recorded for the window: nothing to report.
+        assert_eq!(session_mode_conflict(&modes, "session-2", &view_only), None);
+    }
+
+    #[test]
+    fn links_differing_only_in_tracking_params_share_one_window() {
+        let first =
+            DeepLinkParams::parse(&connect_link("session=s&utm_source=email")).expect("valid link");
+        let resent = DeepLinkParams::parse(&connect_link(
+            "session=s&utm_source=portal&utm_campaign=spring&ref=ticket-9",
+        ))
+        .expect("valid link");
+        assert_ne!(first.url, resent.url);
+        assert_eq!(first.session(), resent.session());
+
+        let mut windows = HashMap::new();
+        // The first copy finds nothing and opens `session-1`…
+        assert_eq!(window_opened_for(&windows, "s"), None);
+        windows.insert("session-1".to_string(), first);
+        // …and the re-sent copy is routed to it instead of a second window.
+        let session = resent.session().expect("session id");
+        assert_eq!(
+            window_opened_for(&windows, session).as_deref(),
+            Some("session-1")
+        );
+        assert_eq!(window_opened_for(&windows, "other"), None);
+    }
+
+    #[test]
+    fn relay_region_is_allowlisted_and_defaults_to_home() {
+        let eu = DeepLinkParams::parse(&connect_link("session=s&relay=eu")).expect("valid link");
+        assert_eq!(eu.relay_region("us"), "eu");
+        let plain = DeepLinkParams::parse(&connect_link("session=s")).expect("valid link");
+        assert_eq!(plain.relay_region("ap"), "ap");
+
+        for relay in ["evil.example.com", "EU", "eu%2Fx"] {
+            let err = DeepLinkParams::parse(&connect_link(&format!("session=s&relay={relay}")))
+                .unwrap_err();
+            assert!(err.contains("relay"), "{relay}: {err}");
+        }
+
+        assert_eq!(relay_region_from_env(None), DEFAULT_RELAY_REGION);
+        assert_eq!(relay_region_from_env(Some(" EU ")), "eu");
+        assert_eq!(relay_region_from_env(Some("mars")), DEFAULT_RELAY_REGION);
+    }
+
+    #[test]
+    fn geometry_must_overlap_a_connected_monitor() {
+        let monitors = [
+            MonitorRect {
+                x: 0.0,
+                y: 0.0,
+                width: 1920.0,
+                height: 1080.0,
+            },
+            MonitorRect {
+                x: 1920.0,
+                y: 0.0,
+                width: 2560.0,
+                height: 1440.0,
+            },
+        ];
+        let at = |x: f64, y: f64| WindowGeometry {
+            x,
+            y,
+            width: 1280.0,
+            height: 800.0,
+        };
+        assert!(geometry_is_visible(&at(100.0, 100.0), &monitors));
+        assert!(geometry_is_visible(&at(2200.0, 300.0), &monitors));
+        // Secondary monitor that has since been unplugged.
+        assert!(!geometry_is_visible(&at(5000.0, 300.0), &monitors));
+        // Only a sliver left on screen.
+        assert!(!geometry_is_visible(&at(4450.0, 300.0), &monitors));
+        assert!(!geometry_is_visible(&at(100.0, -780.0), &monitors));
+        // Nonsense sizes from a corrupt file.
+        let tiny = WindowGeometry {
+            width: 10.0,
+            ..at(100.0, 100.0)
+        };
+        assert!(!geometry_is_visible(&tiny, &monitors));
+        let nan = WindowGeometry {
+            x: f64::NAN,
+            ..at(100.0, 100.0)
+        };
+        assert!(!geometry_is_visible(&nan, &monitors));
+        assert!(!geometry_is_visible(&at(100.0, 100.0), &[]));
+    }
+
+    #[test]
+    fn window_geometry_file_round_trips_and_tolerates_corruption() {
+        let saved = HashMap::from([(
+            "DESKTOP-3OHCUL6".to_string(),
+            WindowGeometry {
+                x: 10.0,
+                y: 20.0,
+                width: 1400.0,
+                height: 900.0,
+            },
+        )]);
+        let json = serde_json::to_string(&saved).unwrap();
+        assert_eq!(parse_window_geometry(&json), saved);
+        assert!(parse_window_geometry("{not json").is_empty());
+        assert!(parse_window_geometry("").is_empty());
+    }
+
+    #[test]
+    fn viewer_settings_fall_back_to_defaults() {
+        let settings = parse_viewer_settings(
+            r#"{"default_width": 2400, "default_height": 1350, "start_maximized": true}"#,
+        );
+        assert_eq!(
+            settings,
+            ViewerSettings {
+                default_width: 2400.0,
+                default_height: 1350.0,
+                start_maximized: true,
+            }
+        );
+        // Missing fields keep their defaults.
+        let partial = parse_viewer_settings(r#"{"start_maximized": true}"#);
+        assert_eq!(partial.default_width, DEFAULT_SESSION_WIDTH);
+        assert!(partial.start_maximized);
+        for contents in ["{not json", "", r#"{"default_width": 10}"#] {
+            let settings = parse_viewer_settings(contents);
+            assert_eq!(settings, ViewerSettings::default(), "{contents}");
+        }
+    }
+
+    #[test]
+    fn saved_sessions_drop_stale_duplicate_and_corrupt_entries() {
+        let saved = |id: &str, last_seen: u64| RestorableSession {
+            session_id: id.to_string(),
+            host_label: None,
+            url: connect_link(&format!("session={id}")),
+            last_seen,
+        };
+        let now = 100_000;
+        let max_age = std::time::Duration::from_secs(15 * 60);
+        let json = serde_json::to_string(&vec![
+            saved("fresh", now - 60),
+            saved("edge", now - 15 * 60),
+            saved("stale", now - 15 * 60 - 1),
+            saved("fresh", now - 30),
+        ])
+        .unwrap();
+        assert_eq!(
+            parse_restorable_sessions(&json, now, max_age),
+            vec![saved("fresh", now - 60), saved("edge", now - 15 * 60)]
+        );
+        assert!(parse_restorable_sessions("{not json", now, max_age).is_empty());
+        assert!(parse_restorable_sessions("", now, max_age).is_empty());
+    }
+
+    #[test]
+    fn restorable_sessions_need_a_link_and_prefer_the_hostname() {
+        let entry = |label: &str, hostname: Option<&str>| SessionEntry {
+            window_label: label.to_string(),
+            hostname: hostname.map(str::to_string),
+        };
+        let sessions = HashMap::from([
+            ("s2".to_string(), entry("session-2", None)),
+            ("s1".to_string(), entry("session-1", Some("PC-1"))),
+            ("unlinked".to_string(), entry("session-3", None)),
+        ]);
+        let link = |id: &str, host: &str| connect_link(&format!("session={id}&host_label={host}"));
+        let links = HashMap::from([
+            ("session-1".to_string(), link("s1", "HOST1")),
+            ("session-2".to_string(), link("s2", "HOST2")),
+        ]);
+        let restorable = restorable_sessions_at(&sessions, &links, 42);
+        let summary: Vec<(&str, Option<&str>, u64)> = restorable
+            .iter()
+            .map(|s| (s.session_id.as_str(), s.host_label.as_deref(), s.last_seen))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("s1", Some("PC-1"), 42), ("s2", Some("HOST2"), 42)]
+        );
+        assert_eq!(restorable[1].url, links["session-2"]);
+    }
+
+    #[test]
+    fn geometry_key_prefers_host_label() {
+        let labelled = DeepLinkParams::parse(&connect_link("session=s1&host_label=HOST")).unwrap();
+        assert_eq!(labelled.geometry_key(), Some("HOST"));
+        let plain = DeepLinkParams::parse(&connect_link("session=s1")).unwrap();
+        assert_eq!(plain.geometry_key(), Some("s1"));
+    }
+
+    #[test]
+    fn early_deep_links_are_buffered_until_setup_drains_them() {
+        let mut queue = EarlyDeepLinkQueue::default();
+        // on_open_url / single-instance firing mid-setup: held, not routed.
+        assert_eq!(queue.offer("breeze://connect?session=a".to_string()), None);
+        assert_eq!(queue.offer("breeze://connect?session=b".to_string()), None);
+
+        assert_eq!(
+            queue.drain(),
+            vec![
+                "breeze://connect?session=a".to_string(),
+                "breeze://connect?session=b".to_string(),
+            ]
+        );
+        // After setup, links pass straight through.
+        assert_eq!(
+            queue.offer("breeze://connect?session=c".to_string()).as_deref(),
+            Some("breeze://connect?session=c")
+        );
+        assert!(queue.drain().is_empty());
+    }
+
+    #[test]
+    fn early_deep_link_queue_drops_oldest_when_full() {
+        let mut queue = EarlyDeepLinkQueue::default();
+        for i in 0..MAX_EARLY_DEEP_LINKS + 2 {
+            assert_eq!(queue.offer(format!("link-{i}")), None);
+        }
+        let drained = queue.drain();
+        assert_eq!(drained.len(), MAX_EARLY_DEEP_LINKS);
+        assert_eq!(drained.first().map(String::as_str), Some("link-2"));
+    }
+
+    #[test]
+    fn next_session_label_skips_labels_still_in_use() {
+        let mut counter = 0;
+        assert_eq!(next_session_label(&mut counter, |_| false), "session-1");
+
+        // session-2 is a live window, session-3 a stale pending deep link.
+        let taken = ["session-2", "session-3"];
+        let label = next_session_label(&mut counter, |l| taken.contains(&l));
+        assert_eq!(label, "session-4");
+        assert_eq!(counter, 4);
+
+        // Wrapping skips 0 rather than producing `session-0`.
+        let mut counter = u64::MAX;
+        assert_eq!(next_session_label(&mut counter, |_| false), "session-1");
+    }
+
+    #[test]
+    fn network_status_is_broadcast_only_on_change() {
+        let mut last = None;
+        assert!(network_status_changed(&mut last, false));
+        assert!(!network_status_changed(&mut last, false));
+        assert!(network_status_changed(&mut last, true));
+        assert!(!network_status_changed(&mut last, true));
+    }
+
+    #[test]
+    fn session_broadcast_deserializes_from_the_frontend_shape() {
+        let event: SessionBroadcast =
+            serde_json::from_value(serde_json::json!({ "kind": "networkStatus", "online": false }))
+                .unwrap();
+        assert!(matches!(
+            event,
+            SessionBroadcast::NetworkStatus { online: false }
+        ));
+    }
+
+    #[test]
+    fn idle_timeout_is_opt_in_and_clamped() {
+        assert_eq!(idle_timeout_from_env(None), None);
+        assert_eq!(idle_timeout_from_env(Some("0")), None);
+        assert_eq!(idle_timeout_from_env(Some("soon")), None);
+        assert_eq!(
+            idle_timeout_from_env(Some(" 30 ")),
+            Some(Duration::from_secs(30 * 60))
+        );
+        assert_eq!(
+            idle_timeout_from_env(Some("1")),
+            Some(Duration::from_secs(MIN_IDLE_TIMEOUT_MINS * 60))
+        );
+        assert_eq!(
+            idle_timeout_from_env(Some("100000")),
+            Some(Duration::from_secs(MAX_IDLE_TIMEOUT_MINS * 60))
+        );
+    }
+
+    #[test]
+    fn idle_actions_warn_once_then_close() {
+        let timeout = Duration::from_secs(600);
+        let start = Instant::now();
+        let mut activity = HashMap::new();
+        for label in ["session-2", "session-1"] {
+            activity.insert(
+                label.to_string(),
+                IdleEntry {
+                    last_activity: start,
+                    warned: false,
+                },
             );
         }
+
+        assert!(idle_actions(&mut activity, start + Duration::from_secs(500), timeout).is_empty());
+        assert_eq!(
+            idle_actions(&mut activity, start + Duration::from_secs(550), timeout),
+            vec![
+                (
+                    "session-1".to_string(),
+                    IdleAction::Warn(Duration::from_secs(50))
+                ),
+                (
+                    "session-2".to_string(),
+                    IdleAction::Warn(Duration::from_secs(50))
+                ),
+            ]
+        );
+        assert!(idle_actions(&mut activity, start + Duration::from_secs(560), timeout).is_empty());
+
+        // Activity resets both the clock and the warning.
+        activity.insert(
+            "session-2".to_string(),
+            IdleEntry {
+                last_activity: start + Duration::from_secs(590),
+                warned: false,
+            },
+        );
+        assert_eq!(
+            idle_actions(&mut activity, start + Duration::from_secs(600), timeout),
+            vec![("session-1".to_string(), IdleAction::Close)]
+        );
+    }
+
+    #[test]
+    fn session_window_event_omits_error_on_success() {
+        let created = SessionWindowEvent {
+            label: "session-1".to_string(),
+            session_id: Some("s1".to_string()),
+            error: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&created).unwrap(),
+            serde_json::json!({ "label": "session-1", "session_id": "s1" })
+        );
+        let failed = SessionWindowEvent {
+            error: Some("boom".to_string()),
+            ..created
+        };
+        assert_eq!(serde_json::to_value(&failed).unwrap()["error"], "boom");
+    }
+
+    #[test]
+    fn max_sessions_from_env_defaults_and_clamps() {
+        assert_eq!(max_sessions_from_env(None), DEFAULT_MAX_SESSIONS);
+        assert_eq!(max_sessions_from_env(Some("4")), 4);
+        assert_eq!(max_sessions_from_env(Some(" 20 ")), 20);
+        assert_eq!(max_sessions_from_env(Some("0")), DEFAULT_MAX_SESSIONS);
+        assert_eq!(max_sessions_from_env(Some("lots")), DEFAULT_MAX_SESSIONS);
+        assert_eq!(max_sessions_from_env(Some("500")), MAX_SESSIONS_CEILING);
+    }
+
+    #[test]
+    fn closed_session_windows_finds_a_closed_but_still_mapped_main() {
+        let entry = |label: &str| SessionEntry {
+            window_label: label.to_string(),
+            hostname: None,
+        };
+        // The main window was closed without `unregister_session`.
+        let sessions = HashMap::from([
+            ("s1".to_string(), entry("main")),
+            ("s2".to_string(), entry("session-2")),
+        ]);
+        let closed = closed_session_windows(&sessions, |label| label != "main");
+        assert_eq!(closed, HashSet::from(["main".to_string()]));
+        assert!(closed_session_windows(&sessions, |_| true).is_empty());
+    }
+
+    #[test]
+    fn session_summaries_skip_closed_windows_and_sort_by_label() {
+        let entry = |label: &str| SessionEntry {
+            window_label: label.to_string(),
+            hostname: None,
+        };
+        let sessions = HashMap::from([
+            ("s10".to_string(), entry("session-10")),
+            ("s2".to_string(), entry("session-2")),
+            ("gone".to_string(), entry("session-3")),
+        ]);
+        let summaries = session_summaries(
+            &sessions,
+            |label| (label != "session-3").then(|| format!("Breeze — {label}")),
+            |label| label == "session-10",
+        );
+        assert_eq!(
+            summaries,
+            vec![
+                SessionSummary {
+                    session_id: "s2".to_string(),
+                    window_label: "session-2".to_string(),
+                    title: "Breeze — session-2".to_string(),
+                    always_on_top: false,
+                },
+                SessionSummary {
+                    session_id: "s10".to_string(),
+                    window_label: "session-10".to_string(),
+                    title: "Breeze — session-10".to_string(),
+                    always_on_top: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn remap_session_keeps_the_window_and_hostname() {
+        let entry = |label: &str| SessionEntry {
+            window_label: label.to_string(),
+            hostname: Some(format!("{label}.corp.test")),
+        };
+        let mut sessions = HashMap::from([
+            ("old".to_string(), entry("session-1")),
+            ("other".to_string(), entry("session-2")),
+        ]);
+
+        // Only the window holding the session may move it.
+        assert!(remap_session(&mut sessions, "session-2", "old", "new").is_err());
+        assert!(sessions.contains_key("old"));
+
+        assert_eq!(
+            remap_session(&mut sessions, "session-1", "old", "new"),
+            Ok(())
+        );
+        assert!(!sessions.contains_key("old"));
+        let moved = &sessions["new"];
+        assert_eq!(moved.window_label, "session-1");
+        assert_eq!(moved.hostname.as_deref(), Some("session-1.corp.test"));
+
+        // Stale or conflicting requests change nothing.
+        assert!(remap_session(&mut sessions, "session-1", "old", "newer").is_err());
+        assert!(remap_session(&mut sessions, "session-1", "new", "other").is_err());
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions["other"].window_label, "session-2");
+    }
+
+    #[test]
+    fn replaced_session_is_relinked_for_routing_and_restore() {
+        let mut sessions = HashMap::from([(
+            "old".to_string(),
+            SessionEntry {
+                window_label: "session-1".to_string(),
+                hostname: None,
+            },
+        )]);
+        let old_link = connect_link("session=old&host_label=PC-1");
+        let mut windows = HashMap::from([(
+            "session-1".to_string(),
+            DeepLinkParams::parse(&old_link).unwrap(),
+        )]);
+        let mut links = HashMap::from([("session-1".to_string(), old_link.clone())]);
+
+        remap_session(&mut sessions, "session-1", "old", "new").unwrap();
+        relink_window_link(&mut windows, "session-1", "old", "new");
+        relink_restore_link(&mut links, "session-1", "old", "new");
+
+        assert_eq!(window_opened_for(&windows, "old"), None);
+        assert_eq!(
+            window_opened_for(&windows, "new").as_deref(),
+            Some("session-1")
+        );
+        let restorable = restorable_sessions_at(&sessions, &links, 42);
+        assert_eq!(restorable.len(), 1);
+        assert_eq!(restorable[0].session_id, "new");
+        assert_eq!(restorable[0].host_label.as_deref(), Some("PC-1"));
+        let restored = DeepLinkParams::parse(&restorable[0].url).unwrap();
+        assert_eq!(restored.session(), Some("new"));
+        assert_eq!(restored.param("code"), Some("c"));
+
+        // A link for some other session is left alone.
+        relink_restore_link(&mut links, "session-1", "old", "newer");
+        let kept = DeepLinkParams::parse(&links["session-1"]).unwrap();
+        assert_eq!(kept.session(), Some("new"));
+    }
+
+    #[test]
+    fn session_window_title_falls_back_and_sanitizes() {
+        let link = DeepLinkParams::parse(&connect_link("session=0123456789abcdef")).unwrap();
+        assert_eq!(link.window_title(), "Breeze — 01234567");
+        let vnc = DeepLinkParams::parse(
+            "breeze://vnc?tunnel=t&device=d&code=c&api=https%3A%2F%2Fapi.example.com",
+        )
+        .unwrap();
+        assert_eq!(vnc.window_title(), DEFAULT_WINDOW_TITLE);
+
+        assert_eq!(session_window_title("  host\u{7}-1 "), "Breeze — host-1");
+        assert_eq!(session_window_title(" \n "), DEFAULT_WINDOW_TITLE);
+        let long = "x".repeat(200);
+        assert_eq!(
+            session_window_title(&long).chars().count(),
+            "Breeze — ".chars().count() + MAX_TITLE_LABEL_CHARS
+        );
+    }
+
+    #[test]
+    fn deep_link_params_reject_malformed_links() {
+        assert!(DeepLinkParams::parse("breeze://connect?session=s").is_err());
+        assert!(DeepLinkParams::parse("https://evil.example/connect?session=s").is_err());
+        assert!(DeepLinkParams::parse("breeze://%%%").is_err());
     }
 
     #[test]
@@ -1603,6 +4335,56 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deep_links_must_use_exactly_the_breeze_scheme_and_a_known_endpoint() {
+        let query = "session=s&code=c&api=https%3A%2F%2Fapi.example.com";
+        for url in [
+            format!("breezemalware://connect?{query}"),
+            format!("breeze-evil:connect?{query}"),
+            format!("breeze.evil://connect?{query}"),
+            format!("xbreeze://connect?{query}"),
+            format!("BREEZE://connect?{query}"),
+        ] {
+            assert_eq!(
+                parse_breeze_deep_link(&url).unwrap_err(),
+                "deep link must use the breeze scheme",
+                "{url}"
+            );
+            assert!(first_deep_link_arg(&[url]).is_none());
+        }
+        for url in [
+            format!("breeze://evil.example/connect?{query}"),
+            format!("breeze://evil.example/../connect?{query}"),
+            format!("breeze://connect/extra?{query}"),
+            format!("breeze:settings?{query}"),
+        ] {
+            assert_eq!(
+                parse_breeze_deep_link(&url).unwrap_err(),
+                "deep link path is not supported",
+                "{url}"
+            );
+        }
+        for url in [
+            format!("breeze://user@connect?{query}"),
+            format!("breeze://connect:8443?{query}"),
+        ] {
+            assert!(parse_breeze_deep_link(&url).is_err(), "{url}");
+        }
+
+        // Every accepted form reads back as the same endpoint and query.
+        for url in [
+            format!("breeze://connect?{query}"),
+            format!("breeze://connect/?{query}"),
+            format!("breeze:connect?{query}"),
+            format!("breeze:///connect?{query}"),
+        ] {
+            let parsed = parse_breeze_deep_link(&url).unwrap();
+            assert_eq!(parsed.path(), "/connect", "{url}");
+            assert_eq!(parsed.query(), Some(query), "{url}");
+            assert!(validate_deep_link(&url).is_ok(), "{url}");
+        }
+    }
+
     #[test]
     fn validate_deep_link_rejects_oversized_parameters() {
         let huge_code = "a".repeat(MAX_CODE_PARAM_BYTES + 1);
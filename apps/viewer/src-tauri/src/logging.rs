@@ -0,0 +1,219 @@
+//! Rotating diagnostic log for the viewer.
+//!
+//! A packaged viewer has no console, so `eprintln!` reached nobody. `init`
+//! installs a `tracing` subscriber that writes to `breeze-viewer.log` under the
+//! platform log directory: deep-link routing decisions, window creation, and
+//! session map changes at `info`, failures at `warn`. Verbosity comes from
+//! `BREEZE_VIEWER_LOG`. The file rotates by size and only one old file is kept.
+//! `recent_lines` reads the tail back for the "copy diagnostics" flow.
+//!
+//! Deep links carry credentials (the connect `code`, signatures), so they are
+//! only ever logged through `redact_link`; session ids stay readable because
+//! they are what support searches for.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_subscriber::filter::LevelFilter;
+
+const LOG_FILE_NAME: &str = "breeze-viewer.log";
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+/// Rotated files kept alongside the live one (`.1` is the newest).
+const KEEP_LOG_FILES: usize = 1;
+
+/// Flushes buffered lines on drop; held for the life of the process.
+static LOG_GUARD: OnceLock<tracing_appender::non_blocking::WorkerGuard> = OnceLock::new();
+/// The live log file, once `init` has opened it.
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Install the file logger. Call once, first thing in setup.
+pub fn init(log_dir: &Path) -> Result<(), String> {
+    let level = level_from_env(std::env::var("BREEZE_VIEWER_LOG").ok().as_deref());
+    std::fs::create_dir_all(log_dir)
+        .map_err(|e| format!("Failed to create log folder {}: {}", log_dir.display(), e))?;
+    let path = log_dir.join(LOG_FILE_NAME);
+    let file = RotatingFile::open(path.clone(), MAX_LOG_BYTES, KEEP_LOG_FILES)
+        .map_err(|e| format!("Failed to open viewer log: {}", e))?;
+    let (writer, guard) = tracing_appender::non_blocking(file);
+    tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_target(false)
+        .try_init()
+        .map_err(|e| format!("Failed to install viewer log: {}", e))?;
+    let _ = LOG_GUARD.set(guard);
+    let _ = LOG_PATH.set(path);
+    Ok(())
+}
+
+/// `BREEZE_VIEWER_LOG` as a level; unset or unrecognized means `info`.
+fn level_from_env(value: Option<&str>) -> LevelFilter {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(LevelFilter::INFO)
+}
+
+/// The last `max` lines written, oldest first, reaching into the rotated file
+/// when the live one is shorter.
+pub fn recent_lines(max: usize) -> Result<Vec<String>, String> {
+    let path = LOG_PATH
+        .get()
+        .ok_or_else(|| "The viewer log is not available".to_string())?;
+    tail_lines(&[rotated_path(path, 1), path.clone()], max)
+        .map_err(|e| format!("Failed to read viewer log: {}", e))
+}
+
+/// The last `max` lines across `paths`, read in order. Missing files count as
+/// empty.
+fn tail_lines(paths: &[PathBuf], max: usize) -> std::io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for path in paths {
+        match std::fs::read(path) {
+            Ok(bytes) => lines.extend(String::from_utf8_lossy(&bytes).lines().map(str::to_string)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let skip = lines.len().saturating_sub(max);
+    Ok(lines.split_off(skip))
+}
+
+/// `url` with credential-bearing query values replaced by `***`: the connect
+/// `code`, link signatures, and anything named like a token, secret or
+/// password. Everything else — session ids included — is kept verbatim.
+pub fn redact_link(url: &str) -> String {
+    let url = url.split_once('#').map_or(url, |(url, _)| url);
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let pairs: Vec<String> = query
+        .split('&')
+        .map(|pair| {
+            let (name, _) = pair.split_once('=').unwrap_or((pair, ""));
+            if is_secret_param(name) {
+                format!("{}=***", name)
+            } else {
+                pair.to_string()
+            }
+        })
+        .collect();
+    format!("{}?{}", base, pairs.join("&"))
+}
+
+fn is_secret_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    ["token", "secret", "password", "signature"]
+        .iter()
+        .any(|secret| name.contains(secret))
+        || matches!(name.as_str(), "code" | "sig" | "key")
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut name = path.to_path_buf().into_os_string();
+    name.push(format!(".{}", n));
+    PathBuf::from(name)
+}
+
+/// Append-only file that rolls over to `<name>.1` once it would pass
+/// `max_bytes`, shifting older files up and deleting the oldest.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_bytes: u64, keep: usize) -> std::io::Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            written,
+            max_bytes,
+            keep,
+        })
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        let _ = std::fs::remove_file(rotated_path(&self.path, self.keep));
+        for n in (1..self.keep).rev() {
+            let _ = std::fs::rename(rotated_path(&self.path, n), rotated_path(&self.path, n + 1));
+        }
+        if self.keep > 0 {
+            std::fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_defaults_to_info() {
+        assert_eq!(level_from_env(None), LevelFilter::INFO);
+        assert_eq!(level_from_env(Some(" debug ")), LevelFilter::DEBUG);
+        assert_eq!(level_from_env(Some("loud")), LevelFilter::INFO);
+    }
+
+    #[test]
+    fn redact_link_keeps_session_ids_and_hides_credentials() {
+        assert_eq!(
+            redact_link(
+                "breeze://connect?session=s1&code=abc&api=https%3A%2F%2Fapi.example.com&sig=def&exp=9&password=p#frag"
+            ),
+            "breeze://connect?session=s1&code=***&api=https%3A%2F%2Fapi.example.com&sig=***&exp=9&password=***"
+        );
+        assert_eq!(redact_link("breeze://connect"), "breeze://connect");
+    }
+
+    #[test]
+    fn rotating_file_rolls_over_and_tail_spans_both_files() {
+        let dir = std::env::temp_dir().join(format!("breeze-viewer-log-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOG_FILE_NAME);
+
+        let mut file = RotatingFile::open(path.clone(), 20, 1).unwrap();
+        for line in ["one\n", "two\n", "three\n", "four\n", "five\n", "six\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() <= 20);
+        assert!(!rotated_path(&path, 2).exists());
+        let files = [rotated_path(&path, 1), path.clone()];
+        assert_eq!(tail_lines(&files, 3).unwrap(), ["four", "five", "six"]);
+        assert_eq!(tail_lines(&files, 100).unwrap().last().unwrap(), "six");
+        let missing = [dir.join("missing.log")];
+        assert!(tail_lines(&missing, 5).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,6 +6,13 @@ import DesktopViewer from './components/DesktopViewer';
 import UpdateIndicator from './components/UpdateIndicator';
 import { parseDeepLink, type ConnectionParams } from './lib/protocol';
 
+/** A session left open by the previous run (`restorable_sessions`). */
+interface RestoreOffer {
+  session_id: string;
+  host_label: string | null;
+  last_seen: number;
+}
+
 /**
  * Main window: hidden, serves as process anchor (Tauri requires at least one window).
  * Session windows: connect via deep link, show DesktopViewer.
@@ -16,6 +23,8 @@ export default function App() {
   const [error, setError] = useState<string | null>(null);
   // Why URL-scheme registration failed, if it did — surfaced on the idle card.
   const [schemeError, setSchemeError] = useState<string | null>(null);
+  // Sessions the previous run left open (crash or update), offered back once.
+  const [restorable, setRestorable] = useState<RestoreOffer[]>([]);
   const lastDeepLinkRef = useRef<{ key: string; at: number } | null>(null);
 
   // Detect window role on mount
@@ -39,6 +48,37 @@ export default function App() {
       });
   }, [windowLabel]);
 
+  useEffect(() => {
+    if (windowLabel !== 'main') return;
+    invoke<RestoreOffer[]>('restorable_sessions')
+      .then(setRestorable)
+      .catch(() => {});
+  }, [windowLabel]);
+
+  // Reopen (`null` = all) or decline (`[]`) the offer; Rust drops it either way.
+  const restoreSessions = useCallback((sessionIds: string[] | null) => {
+    setRestorable([]);
+    invoke('restore_sessions', { sessionIds }).catch(() => {});
+  }, []);
+
+  // The anchor window lives as long as the process, so it alone reports
+  // connectivity changes; Rust fans them out to every session window as
+  // `network-status-changed`.
+  useEffect(() => {
+    if (windowLabel !== 'main') return;
+    const report = () => {
+      invoke('broadcast_session_event', {
+        event: { kind: 'networkStatus', online: navigator.onLine },
+      }).catch(() => {});
+    };
+    window.addEventListener('online', report);
+    window.addEventListener('offline', report);
+    return () => {
+      window.removeEventListener('online', report);
+      window.removeEventListener('offline', report);
+    };
+  }, [windowLabel]);
+
   // ── Session window: deep link polling + events ─────────────────────
   const applyDeepLink = useCallback((url: string) => {
     const parsed = parseDeepLink(url);
@@ -60,7 +100,8 @@ export default function App() {
   useEffect(() => {
     if (windowLabel === 'main') return;
 
-    // Path 1: Poll Rust for pending deep link
+    // Path 1 (fallback): Poll Rust for pending deep link in case the
+    // frontend_ready handshake below is lost.
     let pollCount = 0;
     const maxPolls = 17;
     const pollTimer = setInterval(() => {
@@ -80,9 +121,12 @@ export default function App() {
     // Path 2: Listen for events scoped to THIS window only.
     // Global listen() receives events from all windows — emit_to("session-2")
     // would also trigger session-1's listener, causing cross-window bleed.
+    // Once the listener is registered, tell Rust we're ready so it emits the
+    // pending URL immediately instead of on a guessed delay.
     const unlisten = getCurrentWebviewWindow().listen<string>('deep-link-received', (event) => {
       applyDeepLink(event.payload);
     });
+    unlisten.then(() => invoke('frontend_ready')).catch(() => {});
 
     return () => {
       clearInterval(pollTimer);
@@ -114,6 +158,29 @@ export default function App() {
   // card claiming "ready" while `breeze://` stays unclaimed would send the user
   // straight back into the download loop, now with the UI vouching for it.
   if (windowLabel === 'main') {
+    const restoreCard = restorable.length > 0 && (
+      <div className="mt-2 flex flex-col items-center gap-2 rounded-md border border-gray-700 px-4 py-3">
+        <p className="text-sm text-gray-300">
+          {restorable.length === 1
+            ? `A session to ${restorable[0].host_label ?? 'a device'} was still open.`
+            : `${restorable.length} sessions were still open.`}
+        </p>
+        <div className="flex gap-2">
+          <button
+            className="rounded-md bg-accent px-3 py-1 text-xs font-medium text-white hover:bg-accent-hover"
+            onClick={() => restoreSessions(null)}
+          >
+            Reopen
+          </button>
+          <button
+            className="rounded-md px-3 py-1 text-xs text-gray-400 hover:text-gray-200"
+            onClick={() => restoreSessions([])}
+          >
+            Dismiss
+          </button>
+        </div>
+      </div>
+    );
     if (schemeError) {
       return (
         <div className="flex h-screen flex-col items-center justify-center gap-3 bg-gray-900 px-8 text-center">
@@ -123,6 +190,7 @@ export default function App() {
             Remote sessions won't open automatically on this machine.
           </p>
           <p className="max-w-full break-words text-xs text-gray-500">{schemeError}</p>
+          {restoreCard}
         </div>
       );
     }
@@ -137,6 +205,7 @@ export default function App() {
         <p className="text-xs text-gray-500">
           You can close this window — Breeze reopens the viewer when a session starts.
         </p>
+        {restoreCard}
       </div>
     );
   }
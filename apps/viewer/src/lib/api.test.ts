@@ -39,5 +39,19 @@ describe('api', () => {
     const [url] = fetchMock.mock.calls[0] as [string, RequestInit];
     expect(url).toBe('https://example.com/base/api/v1/desktop-ws/connect/exchange');
   });
+
+  it('exchangeDesktopConnectCode sends the relay region only when given', async () => {
+    const fetchMock = vi.fn(async (_url: string, _init?: RequestInit) => {
+      return new Response(JSON.stringify({ accessToken: 't', expiresInSeconds: 60 }), { status: 200 });
+    });
+    vi.stubGlobal('fetch', fetchMock);
+
+    await exchangeDesktopConnectCode('https://example.com', 's', 'c', 'eu');
+    await exchangeDesktopConnectCode('https://example.com', 's', 'c');
+
+    const bodies = fetchMock.mock.calls.map(([, init]) => JSON.parse(init!.body as string));
+    expect(bodies[0]).toEqual({ sessionId: 's', code: 'c', relay: 'eu' });
+    expect(bodies[1]).toEqual({ sessionId: 's', code: 'c' });
+  });
 });
 
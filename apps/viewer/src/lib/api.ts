@@ -54,15 +54,21 @@ export async function createDesktopSession(
   return resp.json();
 }
 
+/**
+ * Exchange a one-time connect code for an access token. `relay` names the
+ * regional relay the session should route through; omitted, the backend
+ * picks.
+ */
 export async function exchangeDesktopConnectCode(
   apiUrl: string,
   sessionId: string,
-  code: string
+  code: string,
+  relay?: string
 ): Promise<{ accessToken: string; expiresInSeconds: number; hostname?: string | null; osType?: string | null } | null> {
   const resp = await fetch(buildApiUrl(apiUrl, '/api/v1/desktop-ws/connect/exchange'), {
     method: 'POST',
     headers: { 'Content-Type': 'application/json' },
-    body: JSON.stringify({ sessionId, code }),
+    body: JSON.stringify(relay ? { sessionId, code, relay } : { sessionId, code }),
   });
 
   if (!resp.ok) return null;
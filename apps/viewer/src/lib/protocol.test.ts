@@ -100,6 +100,25 @@ describe('parseDeepLink', () => {
     expect(result).not.toBeNull();
     expect(result!).not.toHaveProperty('deviceId');
   });
+
+  it('parses view_only and ignores other extra params', () => {
+    const url =
+      'breeze://connect?session=abc&code=def&api=https%3A%2F%2Fexample.com&view_only=1&relay=eu&host_label=DESKTOP-3OHCUL6';
+    expect(parseDeepLink(url)).toEqual({
+      mode: 'desktop',
+      sessionId: 'abc',
+      connectCode: 'def',
+      apiUrl: 'https://example.com',
+      viewOnly: true,
+    });
+  });
+
+  it('omits viewOnly unless view_only is 1 or true', () => {
+    const url = 'breeze://connect?session=abc&code=def&api=https%3A%2F%2Fexample.com&view_only=0';
+    const result = parseDeepLink(url);
+    expect(result).not.toBeNull();
+    expect(result!).not.toHaveProperty('viewOnly');
+  });
 });
 
 describe('parseDeepLink — VNC', () => {
@@ -11,6 +11,8 @@ export interface DesktopConnectionParams {
   apiUrl: string;
   targetSessionId?: number;
   deviceId?: string;
+  /** `view_only=1`: watch the session without sending local input. */
+  viewOnly?: boolean;
 }
 
 export interface VncConnectionParams {
@@ -86,6 +88,7 @@ function parseDesktopDeepLink(parsed: URL): DesktopConnectionParams | null {
   const apiUrl = parsed.searchParams.get('api');
   const targetSessionIdRaw = parsed.searchParams.get('targetSessionId');
   const deviceIdRaw = parsed.searchParams.get('device');
+  const viewOnlyRaw = parsed.searchParams.get('view_only');
 
   if (!sessionId || !connectCode || !apiUrl) {
     return null;
@@ -114,6 +117,9 @@ function parseDesktopDeepLink(parsed: URL): DesktopConnectionParams | null {
   // Parse optional deviceId
   const deviceId = deviceIdRaw && deviceIdRaw.length > 0 ? deviceIdRaw : undefined;
 
+  // Same rule as the Rust side (DeepLinkParams::view_only)
+  const viewOnly = viewOnlyRaw === '1' || viewOnlyRaw === 'true';
+
   return {
     mode: 'desktop',
     sessionId,
@@ -121,6 +127,7 @@ function parseDesktopDeepLink(parsed: URL): DesktopConnectionParams | null {
     apiUrl: validatedApiUrl,
     ...(targetSessionId != null ? { targetSessionId } : {}),
     ...(deviceId != null ? { deviceId } : {}),
+    ...(viewOnly ? { viewOnly } : {}),
   };
 }
 